@@ -0,0 +1,205 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use log::warn;
+use serde_json::{Map, Value};
+
+/// Schema version written into every config. Bump it and append a step to `MIGRATIONS` when a
+/// change needs more than `#[serde(default)]` to read older files.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// Rotating backups kept next to each config, `<file>.1.bak` being the newest.
+pub const CONFIG_BACKUPS: usize = 5;
+/// The config is saved every 30 seconds, so a new backup is only taken once the newest is this old.
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Fields holding secrets, or the hash and salt that protect them
+const SECRET_FIELDS: [&str; 8] = ["github_pat", "action_api_key", "salt", "hashed_password", "vault", "action_api_key_vault", "github_app_key_vault", "secret_backend"];
+
+type Migration = fn(&mut Map<String, Value>);
+
+// MIGRATIONS[n] takes a config from version n to n + 1
+const MIGRATIONS: [Migration; CONFIG_SCHEMA_VERSION as usize] = [unversioned_to_v1];
+
+/// Configs written before versioning. Fields added without a default made older files fail to
+/// load; they get the values a new install starts with.
+fn unversioned_to_v1(config: &mut Map<String, Value>) {
+    for field in ["repo_name", "name", "action_listener_url"] {
+        config.entry(field).or_insert_with(|| Value::String(String::new()));
+    }
+}
+
+/// Brings a parsed config up to `CONFIG_SCHEMA_VERSION` and returns the version it started from.
+/// Configs from a newer release are refused rather than silently losing their new fields.
+pub fn migrate(config: &mut Value) -> Result<u32, String> {
+    let Some(fields) = config.as_object_mut() else {
+        return Err("The file does not contain a configuration object".to_string());
+    };
+    let version = match fields.get("schema_version") {
+        None => 0,
+        Some(version) => version.as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("Invalid schema version {}", version))?,
+    };
+    if version > CONFIG_SCHEMA_VERSION {
+        return Err(format!("Written by a newer version of ActionAllegro (schema {}, this build reads up to {})", version, CONFIG_SCHEMA_VERSION));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(fields);
+    }
+    fields.insert("schema_version".to_string(), Value::from(CONFIG_SCHEMA_VERSION));
+    Ok(version)
+}
+
+/// Replaces a config migrated from `version` with its new contents, first copying the old file
+/// to `<file>.v<version>.bak` next to it. Returns the backup's path.
+pub fn rewrite_migrated(path: &Path, version: u32, json: &str) -> Result<PathBuf, String> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".v{}.bak", version));
+    let backup = PathBuf::from(name);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    write_atomic(path, json.as_bytes())?;
+    Ok(backup)
+}
+
+/// Replaces `path` so that a crash leaves either the old or the new contents, never a mix: the
+/// data goes to `<file>.tmp`, is flushed to disk and then renamed over the original.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    let temp = PathBuf::from(name);
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+    // The rename itself only survives a crash once the directory is flushed too
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let _ = File::open(dir).and_then(|dir| dir.sync_all());
+    }
+    Ok(())
+}
+
+fn numbered_backup(path: &Path, number: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}.bak", number));
+    PathBuf::from(name)
+}
+
+/// Copies `path` to `<file>.1.bak`, shifting older backups up and dropping the one past `keep`.
+/// Nothing happens while the newest backup is younger than `interval`; `Duration::ZERO` always
+/// takes one. Returns the new backup, if any.
+pub fn rotate_backups(path: &Path, keep: usize, interval: Duration, now: SystemTime) -> Result<Option<PathBuf>, String> {
+    let newest = numbered_backup(path, 1);
+    if keep == 0 || !path.exists() {
+        return Ok(None);
+    }
+    let age = fs::metadata(&newest).and_then(|metadata| metadata.modified()).ok()
+        .map(|modified| now.duration_since(modified).unwrap_or_default());
+    if age.map_or(false, |age| age < interval) {
+        return Ok(None);
+    }
+    for number in (1..keep).rev() {
+        let older = numbered_backup(path, number);
+        if older.exists() {
+            fs::rename(&older, numbered_backup(path, number + 1)).map_err(|e| format!("Failed to rotate {}: {}", older.display(), e))?;
+        }
+    }
+    fs::copy(path, &newest)
+        .and_then(|_| File::options().write(true).open(&newest))
+        .and_then(|backup| backup.set_modified(now)) // Copies may keep the original's time
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    Ok(Some(newest))
+}
+
+/// Saves a config crash-safely, taking a backup of the previous one first if it's due.
+/// A failed backup is logged but doesn't stop the save.
+pub fn save_config(path: &Path, json: &str) -> Result<(), String> {
+    if let Err(e) = rotate_backups(path, CONFIG_BACKUPS, BACKUP_INTERVAL, SystemTime::now()) {
+        warn!("{}", e);
+    }
+    write_atomic(path, json.as_bytes())
+}
+
+/// Puts a backup's `contents` back as the config at `path`, first backing up the config being
+/// replaced. The contents have to be read before anything else saves the config, since saving
+/// can rotate the backups and move a different file to the path the user picked.
+pub fn restore_backup(path: &Path, contents: &[u8], now: SystemTime) -> Result<(), String> {
+    if let Err(e) = rotate_backups(path, CONFIG_BACKUPS, Duration::ZERO, now) {
+        warn!("{}", e);
+    }
+    write_atomic(path, contents)
+}
+
+/// Gives every backup of `path` the secrets of `current` (the config as saved now), so old
+/// passwords and the pre-vault PAT encryption no longer open anything on disk. Backups that
+/// can't be parsed are deleted. Returns how many backups were rewritten.
+pub fn reseal_backups(path: &Path, current: &Value) -> Result<usize, String> {
+    let mut resealed = 0;
+    for backup in list_backups(path) {
+        let modified = fs::metadata(&backup).and_then(|metadata| metadata.modified()).ok();
+        let parsed = fs::read_to_string(&backup).ok().and_then(|text| serde_json::from_str::<Value>(&text).ok());
+        let Some(Value::Object(mut fields)) = parsed else {
+            fs::remove_file(&backup).map_err(|e| format!("Failed to delete {}: {}", backup.display(), e))?;
+            continue;
+        };
+        for field in SECRET_FIELDS {
+            match current.get(field) {
+                Some(value) => fields.insert(field.to_string(), value.clone()),
+                None => fields.remove(field),
+            };
+        }
+        write_atomic(&backup, Value::Object(fields).to_string().as_bytes())?;
+        // Keep the backup's age so rotation and the restore list aren't affected
+        if let Some(modified) = modified {
+            let _ = File::options().write(true).open(&backup).and_then(|file| file.set_modified(modified));
+        }
+        resealed += 1;
+    }
+    Ok(resealed)
+}
+
+/// Changes one top-level field of a saved config without touching the rest, for state that has
+/// to be written while the app is locked and the secrets can't be sealed.
+pub fn set_field(path: &Path, field: &str, value: Value) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut config: Value = serde_json::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    let Some(fields) = config.as_object_mut() else {
+        return Err(format!("{} does not contain a configuration object", path.display()));
+    };
+    fields.insert(field.to_string(), value);
+    write_atomic(path, config.to_string().as_bytes())
+}
+
+/// Backups of `path` (any `<file>.*.bak` next to it, rotating and migration ones), most recent first.
+pub fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let Some(file_name) = path.file_name().map(|name| name.to_string_lossy().into_owned()) else { return Vec::new() };
+    let prefix = format!("{}.", file_name);
+    let mut backups: Vec<(SystemTime, PathBuf)> = path.parent()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    backups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Moves an unreadable config out of the way so the app can start over, keeping it for inspection.
+pub fn set_aside(path: &Path, timestamp: u64) -> Result<PathBuf, String> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".broken-{}", timestamp));
+    let target = PathBuf::from(name);
+    fs::rename(path, &target).map_err(|e| format!("Failed to move {} aside: {}", path.display(), e))?;
+    Ok(target)
+}
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use reqwest::blocking::Client;
+use serde_json::Value;
+
+pub const GITHUB_URL: &str = "https://github.com";
+/// What the app needs: dispatching and reading workflows, and pushing changes to them.
+pub const DEVICE_SCOPES: &str = "repo workflow";
+
+/// Hand-off slot for the token a background sign-in ends with.
+pub type SharedDeviceFlowResult = Arc<Mutex<Option<Result<String, String>>>>;
+
+/// What the user needs to approve the sign-in in their browser.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PollResult {
+    Pending,
+    SlowDown(u64), // GitHub's new minimum polling interval in seconds
+    Token(String),
+}
+
+/// OAuth device authorization flow against GitHub, or a stub server standing in for it.
+pub struct DeviceFlow {
+    base_url: String,
+    client_id: String,
+    client: Client,
+}
+
+impl DeviceFlow {
+    pub fn new(base_url: &str, client_id: &str) -> Self {
+        DeviceFlow { base_url: base_url.trim_end_matches('/').to_string(), client_id: client_id.to_string(), client: Client::new() }
+    }
+
+    fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<Value, String> {
+        let response = self.client.post(format!("{}{}", self.base_url, path))
+            .header("User-Agent", "reqwest")
+            .header("Accept", "application/json")
+            .form(form)
+            .send()
+            .map_err(|e| format!("Could not reach GitHub: {}", e))?;
+        let status = response.status();
+        let json: Value = response.json().map_err(|e| format!("Unexpected answer from GitHub: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("GitHub refused the sign-in ({}): {}", status, json["error_description"].as_str().or(json["message"].as_str()).unwrap_or_default()));
+        }
+        Ok(json)
+    }
+
+    pub fn request_code(&self, scope: &str) -> Result<DeviceCode, String> {
+        let json = self.post("/login/device/code", &[("client_id", &self.client_id), ("scope", scope)])?;
+        if let Some(error) = json["error"].as_str() {
+            return Err(format!("GitHub refused the sign-in: {}", json["error_description"].as_str().unwrap_or(error)));
+        }
+        serde_json::from_value(json).map_err(|e| format!("Unexpected answer from GitHub: {}", e))
+    }
+
+    /// Asks once whether the user has approved the code yet.
+    pub fn poll(&self, device_code: &str) -> Result<PollResult, String> {
+        let json = self.post("/login/oauth/access_token", &[
+            ("client_id", &self.client_id),
+            ("device_code", device_code),
+            ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+        ])?;
+        if let Some(token) = json["access_token"].as_str() {
+            return Ok(PollResult::Token(token.to_string()));
+        }
+        match json["error"].as_str() {
+            Some("authorization_pending") => Ok(PollResult::Pending),
+            Some("slow_down") => Ok(PollResult::SlowDown(json["interval"].as_u64().unwrap_or(5))),
+            Some("expired_token") => Err("The code expired; start the sign-in again".to_string()),
+            Some("access_denied") => Err("The sign-in was cancelled on GitHub".to_string()),
+            Some(error) => Err(format!("Sign-in failed: {}", json["error_description"].as_str().unwrap_or(error))),
+            None => Err("Unexpected answer from GitHub".to_string()),
+        }
+    }
+
+    /// Polls at the interval GitHub asks for until the user approves the code, the code expires
+    /// or `cancelled` is set.
+    pub fn wait_for_token(&self, code: &DeviceCode, cancelled: &AtomicBool) -> Result<String, String> {
+        let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+        let mut interval = code.interval;
+        loop {
+            thread::sleep(Duration::from_secs(interval));
+            if cancelled.load(Ordering::Relaxed) {
+                return Err("Sign-in cancelled".to_string());
+            }
+            if Instant::now() >= deadline {
+                return Err("The code expired; start the sign-in again".to_string());
+            }
+            match self.poll(&code.device_code)? {
+                PollResult::Pending => {},
+                PollResult::SlowDown(new_interval) => interval = new_interval,
+                PollResult::Token(token) => return Ok(token),
+            }
+        }
+    }
+}
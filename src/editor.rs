@@ -0,0 +1,121 @@
+use std::path::Path;
+use egui::text::LayoutJob;
+use egui::{Color32, FontId, TextFormat};
+
+pub const WORKFLOWS_DIR: &str = ".github/workflows";
+
+/// Lists workflow files of a local clone as paths relative to the repository root, sorted.
+pub fn list_workflow_files(repo_path: &str) -> Vec<String> {
+    let workflows_dir = Path::new(repo_path).join(WORKFLOWS_DIR);
+    let mut files: Vec<String> = walkdir::WalkDir::new(workflows_dir)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| matches!(entry.path().extension().and_then(|ext| ext.to_str()), Some("yml") | Some("yaml")))
+        .map(|entry| format!("{}/{}", WORKFLOWS_DIR, entry.file_name().to_string_lossy()))
+        .collect();
+    files.sort();
+    files
+}
+
+struct YamlColors {
+    key: Color32,
+    string: Color32,
+    comment: Color32,
+    expression: Color32,
+    plain: Color32,
+}
+
+impl YamlColors {
+    fn new(dark_mode: bool) -> Self {
+        if dark_mode {
+            YamlColors {
+                key: Color32::from_rgb(121, 192, 255),
+                string: Color32::from_rgb(165, 214, 255),
+                comment: Color32::from_rgb(139, 148, 158),
+                expression: Color32::from_rgb(255, 166, 87),
+                plain: Color32::from_rgb(201, 209, 217),
+            }
+        } else {
+            YamlColors {
+                key: Color32::from_rgb(0, 92, 197),
+                string: Color32::from_rgb(3, 47, 98),
+                comment: Color32::from_rgb(106, 115, 125),
+                expression: Color32::from_rgb(227, 98, 9),
+                plain: Color32::from_rgb(36, 41, 46),
+            }
+        }
+    }
+}
+
+/// Line based YAML highlighting for the editor: keys, quoted strings, comments and `${{ }}` expressions.
+pub fn highlight_yaml(text: &str, dark_mode: bool) -> LayoutJob {
+    let colors = YamlColors::new(dark_mode);
+    let font = FontId::monospace(13.0);
+    let mut job = LayoutJob::default();
+    let mut append = |job: &mut LayoutJob, segment: &str, color: Color32| {
+        if !segment.is_empty() {
+            job.append(segment, 0.0, TextFormat::simple(font.clone(), color));
+        }
+    };
+
+    for line in text.split_inclusive('\n') {
+        let (content, comment) = split_comment(line);
+
+        // A key is everything up to the first `: ` (or trailing `:`) after indentation and list dashes
+        let indent_len = content.len() - content.trim_start_matches([' ', '-']).len();
+        let key_end = content[indent_len..].find(": ")
+            .or_else(|| content[indent_len..].trim_end().strip_suffix(':').map(str::len))
+            .map(|i| indent_len + i)
+            .filter(|&end| !content[indent_len..end].contains(['"', '\'', '{', '$']));
+
+        let value_start = match key_end {
+            Some(end) => {
+                append(&mut job, &content[..indent_len], colors.plain);
+                append(&mut job, &content[indent_len..end], colors.key);
+                end
+            },
+            None => 0,
+        };
+        append_value(&mut job, &content[value_start..], &colors, &mut append);
+        append(&mut job, comment, colors.comment);
+    }
+
+    job
+}
+
+fn append_value(job: &mut LayoutJob, value: &str, colors: &YamlColors, append: &mut impl FnMut(&mut LayoutJob, &str, Color32)) {
+    let mut rest = value;
+    while !rest.is_empty() {
+        if let Some(start) = rest.find("${{") {
+            let end = rest[start..].find("}}").map_or(rest.len(), |e| start + e + 2);
+            append_plain_or_string(job, &rest[..start], colors, append);
+            append(job, &rest[start..end], colors.expression);
+            rest = &rest[end..];
+        } else {
+            append_plain_or_string(job, rest, colors, append);
+            break;
+        }
+    }
+}
+
+fn append_plain_or_string(job: &mut LayoutJob, segment: &str, colors: &YamlColors, append: &mut impl FnMut(&mut LayoutJob, &str, Color32)) {
+    let trimmed = segment.trim_start_matches([' ', ':']);
+    let is_string = trimmed.starts_with('"') || trimmed.starts_with('\'');
+    append(job, segment, if is_string { colors.string } else { colors.plain });
+}
+
+// Splits off a trailing `# comment`, ignoring `#` inside quotes
+fn split_comment(line: &str) -> (&str, &str) {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) if i == 0 || line[..i].ends_with(' ') => return (&line[..i], &line[i..]),
+            _ => {}
+        }
+    }
+    (line, "")
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+/// Folder names from the root down to a folder; the root itself is the empty path.
+pub type FolderPath = Vec<String>;
+
+pub const ROOT_NAME: &str = "/";
+
+/// A workflow as filed in a folder. `id` and `path` identify it; `name` is only for display
+/// and can change whenever the YAML is edited.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(from = "StoredWorkflowRef")]
+pub struct WorkflowRef {
+    pub id: u64,
+    pub path: String,
+    pub name: String,
+    /// `active`, `disabled_manually`, ... as of the last fetch. Not saved.
+    #[serde(skip)]
+    pub state: String,
+    /// `owner/repo` the workflow belongs to. Only set in workspace folders, which mix repositories.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub repo: String,
+}
+
+impl WorkflowRef {
+    pub fn new(id: u64, path: &str, name: &str) -> Self {
+        WorkflowRef { id, path: path.to_string(), name: name.to_string(), state: String::new(), repo: String::new() }
+    }
+
+    /// A workflow only known by name, from configs written before folders tracked ids.
+    /// It gets its id and path the next time workflows are fetched.
+    pub fn legacy(name: &str) -> Self {
+        WorkflowRef::new(0, "", name)
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.id == 0
+    }
+}
+
+// Older folder trees stored plain workflow names
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StoredWorkflowRef {
+    Name(String),
+    Full { id: u64, #[serde(default)] path: String, #[serde(default)] name: String, #[serde(default)] repo: String },
+}
+
+impl From<StoredWorkflowRef> for WorkflowRef {
+    fn from(stored: StoredWorkflowRef) -> Self {
+        match stored {
+            StoredWorkflowRef::Name(name) => WorkflowRef::legacy(&name),
+            StoredWorkflowRef::Full { id, path, name, repo } => WorkflowRef { repo, ..WorkflowRef::new(id, &path, &name) },
+        }
+    }
+}
+
+/// What changed between the folders and the workflows GitHub returned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcileReport {
+    pub added: Vec<String>,
+    /// Old and new display names.
+    pub renamed: Vec<(String, String)>,
+    pub removed: Vec<String>,
+}
+
+impl ReconcileReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.renamed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A folder of workflows with nested subfolders. Every workflow lives in exactly one
+/// folder; the root holds the ones that have not been filed anywhere else.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct FolderNode {
+    pub name: String,
+    #[serde(default)]
+    pub workflows: Vec<WorkflowRef>,
+    #[serde(default)]
+    pub children: Vec<FolderNode>,
+    #[serde(default)]
+    pub expanded: bool,
+}
+
+impl Default for FolderNode {
+    fn default() -> Self {
+        FolderNode::new(ROOT_NAME)
+    }
+}
+
+impl FolderNode {
+    pub fn new(name: &str) -> Self {
+        FolderNode { name: name.to_string(), workflows: Vec::new(), children: Vec::new(), expanded: false }
+    }
+
+    /// Builds a tree from the old flat `folders` map, where `/` could also list workflows
+    /// that were filed elsewhere. Names written as `A/B/C` become nested folders.
+    pub fn from_flat(folders: &HashMap<String, Vec<String>>) -> Self {
+        let mut root = FolderNode::default();
+
+        let mut names: Vec<&String> = folders.keys().filter(|name| name.as_str() != ROOT_NAME).collect();
+        names.sort();
+        for name in names {
+            let path: FolderPath = name.split('/').map(str::trim).filter(|part| !part.is_empty()).map(String::from).collect();
+            let mut node = &mut root;
+            for part in &path {
+                let index = match node.children.iter().position(|child| &child.name == part) {
+                    Some(index) => index,
+                    None => {
+                        node.children.push(FolderNode::new(part));
+                        node.children.len() - 1
+                    }
+                };
+                node = &mut node.children[index];
+            }
+            for workflow in &folders[name] {
+                if !node.workflows.iter().any(|w| &w.name == workflow) {
+                    node.workflows.push(WorkflowRef::legacy(workflow));
+                }
+            }
+        }
+
+        for workflow in folders.get(ROOT_NAME).into_iter().flatten() {
+            if !root.contains_name(workflow) {
+                root.workflows.push(WorkflowRef::legacy(workflow));
+            }
+        }
+        root
+    }
+
+    fn contains_name(&self, name: &str) -> bool {
+        self.workflows.iter().any(|w| w.name == name) || self.children.iter().any(|child| child.contains_name(name))
+    }
+
+    /// Path of the folder a workflow is filed in.
+    pub fn folder_of(&self, id: u64) -> Option<FolderPath> {
+        if self.workflows.iter().any(|w| w.id == id) {
+            return Some(Vec::new());
+        }
+        self.children.iter().find_map(|child| {
+            let mut path = child.folder_of(id)?;
+            path.insert(0, child.name.clone());
+            Some(path)
+        })
+    }
+
+    pub fn contains_workflow(&self, id: u64) -> bool {
+        self.workflows.iter().any(|w| w.id == id) || self.children.iter().any(|child| child.contains_workflow(id))
+    }
+
+    /// Matches filed workflows against a fresh fetch: first by id, then by file path, and for
+    /// legacy name-only entries by name. Matched entries pick up the current id, path and
+    /// name, entries with no match are dropped, and unmatched fetched workflows go to the root.
+    pub fn reconcile(&mut self, fetched: &[WorkflowRef]) -> ReconcileReport {
+        fn visit(node: &mut FolderNode, fetched: &[WorkflowRef], claimed: &mut [bool], report: &mut ReconcileReport) {
+            node.workflows.retain_mut(|stored| {
+                let unclaimed = |i: &usize| !claimed[*i];
+                let found = (0..fetched.len()).filter(unclaimed).find(|&i| !stored.is_legacy() && fetched[i].id == stored.id)
+                    .or_else(|| (0..fetched.len()).filter(unclaimed).find(|&i| !stored.path.is_empty() && fetched[i].path == stored.path))
+                    .or_else(|| (0..fetched.len()).filter(unclaimed).find(|&i| stored.is_legacy() && fetched[i].name == stored.name));
+                match found {
+                    Some(i) => {
+                        claimed[i] = true;
+                        if !stored.is_legacy() && stored.name != fetched[i].name {
+                            report.renamed.push((stored.name.clone(), fetched[i].name.clone()));
+                        }
+                        *stored = fetched[i].clone();
+                        true
+                    },
+                    None => {
+                        report.removed.push(stored.name.clone());
+                        false
+                    },
+                }
+            });
+            for child in node.children.iter_mut() {
+                visit(child, fetched, claimed, report);
+            }
+        }
+
+        let mut report = ReconcileReport::default();
+        let mut claimed = vec![false; fetched.len()];
+        visit(self, fetched, &mut claimed, &mut report);
+
+        for (workflow, _) in fetched.iter().zip(claimed).filter(|(_, claimed)| !claimed) {
+            report.added.push(workflow.name.clone());
+            self.workflows.push(workflow.clone());
+        }
+        report
+    }
+
+    /// Flattens the tree back into the old name based map format, joining nested names with `/`.
+    pub fn to_flat(&self) -> HashMap<String, Vec<String>> {
+        fn names(node: &FolderNode) -> Vec<String> {
+            node.workflows.iter().map(|w| w.name.clone()).collect()
+        }
+
+        fn collect(node: &FolderNode, prefix: &str, flat: &mut HashMap<String, Vec<String>>) {
+            for child in &node.children {
+                let name = if prefix.is_empty() { child.name.clone() } else { format!("{}/{}", prefix, child.name) };
+                flat.insert(name.clone(), names(child));
+                collect(child, &name, flat);
+            }
+        }
+
+        let mut flat = HashMap::new();
+        flat.insert(ROOT_NAME.to_string(), names(self));
+        collect(self, "", &mut flat);
+        flat
+    }
+
+    pub fn get(&self, path: &[String]) -> Option<&FolderNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.children.iter().find(|child| &child.name == first)?.get(rest),
+        }
+    }
+
+    pub fn get_mut(&mut self, path: &[String]) -> Option<&mut FolderNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.children.iter_mut().find(|child| &child.name == first)?.get_mut(rest),
+        }
+    }
+
+    pub fn add_folder(&mut self, parent: &[String], name: &str) -> Result<FolderPath, String> {
+        let name = name.trim();
+        if name.is_empty() || name.contains('/') {
+            return Err(format!("'{}' is not a valid folder name", name));
+        }
+        let parent_node = self.get_mut(parent).ok_or("Parent folder no longer exists")?;
+        if parent_node.children.iter().any(|child| child.name == name) {
+            return Err(format!("A folder named '{}' already exists here", name));
+        }
+        parent_node.children.push(FolderNode::new(name));
+        parent_node.children.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        parent_node.expanded = true;
+
+        let mut path = parent.to_vec();
+        path.push(name.to_string());
+        Ok(path)
+    }
+
+    /// Files a workflow into `target`, moving it if the tree already holds it.
+    pub fn file_workflow(&mut self, workflow: WorkflowRef, target: &[String]) {
+        if self.contains_workflow(workflow.id) {
+            self.move_workflow(workflow.id, target);
+        } else if let Some(folder) = self.get_mut(target) {
+            folder.workflows.push(workflow);
+        }
+    }
+
+    pub fn remove_workflow(&mut self, id: u64) {
+        self.workflows.retain(|w| w.id != id);
+        for child in self.children.iter_mut() {
+            child.remove_workflow(id);
+        }
+    }
+
+    /// Updates names and paths of the entries from `repo` after a fetch of that repository and
+    /// drops the ones it no longer has. Entries from other repositories are left alone.
+    pub fn sync_repo(&mut self, repo: &str, fetched: &[WorkflowRef]) {
+        self.workflows.retain_mut(|stored| {
+            if stored.repo != repo {
+                return true;
+            }
+            match fetched.iter().find(|w| w.id == stored.id) {
+                Some(workflow) => {
+                    stored.name.clone_from(&workflow.name);
+                    stored.path.clone_from(&workflow.path);
+                    true
+                },
+                None => false,
+            }
+        });
+        for child in self.children.iter_mut() {
+            child.sync_repo(repo, fetched);
+        }
+    }
+
+    /// Files a workflow into `target`, taking it out of whichever folder held it before.
+    pub fn move_workflow(&mut self, id: u64, target: &[String]) {
+        fn take(node: &mut FolderNode, id: u64) -> Option<WorkflowRef> {
+            if let Some(index) = node.workflows.iter().position(|w| w.id == id) {
+                return Some(node.workflows.remove(index));
+            }
+            node.children.iter_mut().find_map(|child| take(child, id))
+        }
+
+        if self.get(target).is_none() {
+            return;
+        }
+        if let Some(workflow) = take(self, id) {
+            if let Some(folder) = self.get_mut(target) {
+                folder.workflows.push(workflow);
+            }
+        }
+    }
+
+    /// Moves the folder at `from` (with everything inside it) under `to_parent`.
+    pub fn move_folder(&mut self, from: &[String], to_parent: &[String]) -> Result<FolderPath, String> {
+        let Some((name, from_parent)) = from.split_last() else {
+            return Err("The root folder cannot be moved".to_string());
+        };
+        if to_parent.starts_with(from) {
+            return Err("A folder cannot be moved into itself".to_string());
+        }
+        if to_parent == from_parent {
+            return Ok(from.to_vec());
+        }
+        let target = self.get(to_parent).ok_or("Target folder no longer exists")?;
+        if target.children.iter().any(|child| &child.name == name) {
+            return Err(format!("A folder named '{}' already exists there", name));
+        }
+
+        let source_parent = self.get_mut(from_parent).ok_or("Folder no longer exists")?;
+        let index = source_parent.children.iter().position(|child| &child.name == name).ok_or("Folder no longer exists")?;
+        let node = source_parent.children.remove(index);
+
+        let target = self.get_mut(to_parent).ok_or("Target folder no longer exists")?;
+        target.children.push(node);
+        target.children.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        target.expanded = true;
+
+        let mut path = to_parent.to_vec();
+        path.push(name.clone());
+        Ok(path)
+    }
+}
@@ -0,0 +1,104 @@
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine};
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde_json::json;
+use zeroize::Zeroize;
+
+/// How a profile authenticates to GitHub.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthMethod {
+    #[default]
+    PersonalToken,
+    GitHubApp,
+}
+
+impl AuthMethod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuthMethod::PersonalToken => "Personal access token",
+            AuthMethod::GitHubApp => "GitHub App",
+        }
+    }
+}
+
+/// The GitHub App a profile authenticates as. Its private key is kept with the other secrets.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct GitHubAppConfig {
+    pub app_id: String,
+    pub installation_id: String,
+}
+
+// Installation tokens live for an hour; a new one is fetched this long before that
+const REFRESH_MARGIN_SECS: u64 = 5 * 60;
+
+/// Short-lived token for one installation, used like a PAT for REST calls and git.
+pub struct InstallationToken {
+    pub token: String,
+    pub expires_at: u64, // Seconds since the epoch
+}
+
+impl InstallationToken {
+    pub fn needs_refresh(&self, now: u64) -> bool {
+        now + REFRESH_MARGIN_SECS >= self.expires_at
+    }
+}
+
+impl Drop for InstallationToken {
+    fn drop(&mut self) {
+        self.token.zeroize();
+    }
+}
+
+/// Reads the app's private key as GitHub hands it out (PKCS#1 PEM); PKCS#8 PEM works too.
+pub fn load_private_key(pem: &str) -> Result<RsaKeyPair, String> {
+    let (label, der) = decode_pem(pem)?;
+    match label.as_str() {
+        "RSA PRIVATE KEY" => RsaKeyPair::from_der(&der),
+        "PRIVATE KEY" => RsaKeyPair::from_pkcs8(&der),
+        other => return Err(format!("Expected an RSA private key, found a '{}' block", other)),
+    }
+    .map_err(|e| format!("Invalid GitHub App private key: {}", e))
+}
+
+fn decode_pem(pem: &str) -> Result<(String, Vec<u8>), String> {
+    let missing = || "The private key is not in PEM format".to_string();
+    let start = pem.find("-----BEGIN ").ok_or_else(missing)? + "-----BEGIN ".len();
+    let label_len = pem[start..].find("-----").ok_or_else(missing)?;
+    let label = &pem[start..start + label_len];
+    let body_start = start + label_len + "-----".len();
+    let body_len = pem[body_start..].find(&format!("-----END {}-----", label)).ok_or_else(missing)?;
+    let body: String = pem[body_start..body_start + body_len].split_whitespace().collect();
+    let der = STANDARD.decode(body).map_err(|e| format!("Invalid private key encoding: {}", e))?;
+    Ok((label.to_string(), der))
+}
+
+/// Signs the JWT the app exchanges for installation tokens. GitHub accepts at most ten minutes of
+/// validity, and `iat` is backdated a minute to allow for clock drift.
+pub fn app_jwt(app_id: &str, key: &RsaKeyPair, now: u64) -> Result<String, String> {
+    let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256", "typ": "JWT" }).to_string());
+    let claims = URL_SAFE_NO_PAD.encode(json!({ "iat": now.saturating_sub(60), "exp": now + 9 * 60, "iss": app_id }).to_string());
+    let signing_input = format!("{}.{}", header, claims);
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(&RSA_PKCS1_SHA256, &SystemRandom::new(), signing_input.as_bytes(), &mut signature)
+        .map_err(|_| "Failed to sign the GitHub App JWT".to_string())?;
+    Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Seconds since the epoch for a UTC timestamp as GitHub writes them (`2024-07-11T22:14:10Z`).
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let date: Vec<i64> = date.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<u64> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let (&[year, month, day], &[hours, minutes, seconds]) = (date.as_slice(), time.as_slice()) else { return None };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days since the epoch from a civil date (Howard Hinnant's algorithm)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146_097 + doe - 719_468).ok()?;
+    Some(days * 86_400 + hours * 3_600 + minutes * 60 + seconds)
+}
@@ -7,18 +7,29 @@ use git2;
 use git2::{Commit, Cred, FetchOptions, PushOptions, RemoteCallbacks, Repository, BranchType};
 use git2::build::RepoBuilder;
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::thread;
 use git2::Error as Git2Error; // Assuming you're using the git2 crate
 
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, LINK};
-
-pub fn get_actions(repo: &str, token: &str) -> Result<HashMap<String, u64>, Box<dyn Error>> {
-    println!("Fetching actions for repository: {}", repo);
+use crate::folders::WorkflowRef;
+use crate::inventory::{Inventory, InventoryRow, SharedInventoryResult};
+use crate::workflow::Workflow;
+use crate::token_check::{RepoPermissions, TokenReport};
+use crate::github_app::{parse_timestamp, InstallationToken};
+use log::{debug, info};
+
+// Installation tokens only work for git with this user name; PATs accept any
+const GIT_TOKEN_USER: &str = "x-access-token";
+
+/// Lists every workflow in the repository. Names are not unique, so callers should key on id or path.
+pub fn get_actions(repo: &str, token: &str) -> Result<Vec<WorkflowRef>, Box<dyn Error>> {
+    debug!("Fetching actions for repository: {}", repo);
     let base_url = format!("https://api.github.com/repos/{}/actions/workflows", repo);
     let client = Client::new();
 
-    let mut actions = HashMap::new();
+    let mut actions = Vec::new();
     let mut next_page_url = Some(base_url);
 
     while let Some(url) = next_page_url {
@@ -37,7 +48,9 @@ pub fn get_actions(repo: &str, token: &str) -> Result<HashMap<String, u64>, Box<
         if let Some(workflows) = json["workflows"].as_array() {
             for workflow in workflows {
                 if let (Some(name), Some(id)) = (workflow["name"].as_str(), workflow["id"].as_u64()) {
-                    actions.insert(name.to_string(), id);
+                    let mut action = WorkflowRef::new(id, workflow["path"].as_str().unwrap_or_default(), name);
+                    action.state = workflow["state"].as_str().unwrap_or_default().to_string();
+                    actions.push(action);
                 }
             }
         }
@@ -68,7 +81,7 @@ fn extract_next_page_url(link_header: &str) -> Option<String> {
 pub fn get_workflow_details(repo: &str, token: &str, workflow_id: &Option<u64>) -> Result<Value, Box<dyn Error>> {
     match workflow_id {
         Some(id) => {
-            println!("Fetching details for workflow: {:?}", id);
+            debug!("Fetching details for workflow: {:?}", id);
             let url = format!("https://api.github.com/repos/{}/actions/workflows/{:?}", repo, id);
 
             let client = reqwest::blocking::Client::new();
@@ -90,14 +103,272 @@ pub fn get_workflow_details(repo: &str, token: &str, workflow_id: &Option<u64>)
 }
 
 
+pub fn get_workflow_runs(repo: &str, token: &str, workflow_id: u64) -> Result<Vec<Value>, Box<dyn Error>> {
+    debug!("Fetching runs for workflow: {}", workflow_id);
+    let url = format!("https://api.github.com/repos/{}/actions/workflows/{}/runs?per_page=20", repo, workflow_id);
+
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(url)
+        .header("User-Agent", "reqwest")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(response.text()?.into());
+    }
+
+    let json: Value = response.json()?;
+    Ok(json["workflow_runs"].as_array().cloned().unwrap_or_default())
+}
+
+/// Conclusion of the most recent run of each workflow in the repository, keyed by workflow id.
+/// Runs still in progress report their status instead.
+pub fn get_latest_run_conclusions(repo: &str, token: &str, workflow_ids: &[u64]) -> Result<HashMap<u64, String>, Box<dyn Error>> {
+    Ok(get_latest_runs(repo, token, workflow_ids)?.into_iter().map(|(id, (conclusion, _))| (id, conclusion)).collect())
+}
+
+/// Conclusion (or status, while in progress) and creation date of the most recent run of each
+/// of `workflow_ids`, keyed by workflow id. Workflows that never ran are left out.
+pub fn get_latest_runs(repo: &str, token: &str, workflow_ids: &[u64]) -> Result<HashMap<u64, (String, String)>, Box<dyn Error>> {
+    debug!("Fetching latest runs for repository: {}", repo);
+    let client = Client::new();
+    let mut runs = HashMap::new();
+    // One page of the repository's runs covers the busy workflows; the rest are asked for one by one
+    collect_latest_runs(&client, &format!("https://api.github.com/repos/{}/actions/runs?per_page=100", repo), token, &mut runs)?;
+    for workflow_id in workflow_ids {
+        if !runs.contains_key(workflow_id) {
+            collect_latest_runs(&client, &format!("https://api.github.com/repos/{}/actions/workflows/{}/runs?per_page=1", repo, workflow_id), token, &mut runs)?;
+        }
+    }
+    Ok(runs)
+}
+
+fn collect_latest_runs(client: &Client, url: &str, token: &str, runs: &mut HashMap<u64, (String, String)>) -> Result<(), Box<dyn Error>> {
+    let response = client.get(url)
+        .header("User-Agent", "reqwest")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e.into());
+    }
+    if !response.status().is_success() {
+        return Err(response.text()?.into());
+    }
+
+    let json: Value = response.json()?;
+    // Runs come back newest first, so the first one seen per workflow wins
+    for run in json["workflow_runs"].as_array().into_iter().flatten() {
+        let Some(workflow_id) = run["workflow_id"].as_u64() else { continue };
+        let conclusion = run["conclusion"].as_str().or(run["status"].as_str()).unwrap_or_default();
+        let created_at = run["created_at"].as_str().unwrap_or_default();
+        runs.entry(workflow_id).or_insert_with(|| (conclusion.to_string(), created_at.to_string()));
+    }
+    Ok(())
+}
+
+// GitHub answers 429, or 403 with no requests remaining, once the token's rate limit is used up
+fn rate_limit_error(response: &reqwest::blocking::Response) -> Option<String> {
+    let headers = response.headers();
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let limited = response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || (response.status() == reqwest::StatusCode::FORBIDDEN && (header("x-ratelimit-remaining") == Some("0") || header("retry-after").is_some()));
+    if !limited {
+        return None;
+    }
+    Some(match header("retry-after") {
+        Some(seconds) => format!("GitHub API rate limit exceeded, retry in {} seconds", seconds),
+        None => match header("x-ratelimit-reset").and_then(|reset| reset.parse::<u64>().ok()) {
+            // The reset is a Unix timestamp
+            Some(reset) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+                format!("GitHub API rate limit exceeded, resets in {} minutes", reset.saturating_sub(now).div_ceil(60))
+            },
+            None => "GitHub API rate limit exceeded".to_string(),
+        },
+    })
+}
+
+/// Checks what a token can do: who it belongs to, its scopes and expiry, and its access to `repo`
+/// when one is configured.
+pub fn check_token(repo: &str, token: &str) -> Result<TokenReport, Box<dyn Error>> {
+    debug!("Checking token capabilities for repository: {}", repo);
+    let client = Client::new();
+
+    let response = client.get("https://api.github.com/user")
+        .header("User-Agent", "reqwest")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("GitHub rejected the token: it is invalid, expired or revoked".into());
+    }
+    if !response.status().is_success() {
+        return Err(format!("GitHub returned {} for the token check", response.status()).into());
+    }
+    let header = |name: &str| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+    let scopes = header("x-oauth-scopes");
+    let expires_at = header("github-authentication-token-expiration");
+    let user: Value = response.json()?;
+    let login = user["login"].as_str().unwrap_or("unknown").to_string();
+
+    let repo_permissions = if repo.is_empty() {
+        None
+    } else {
+        let response = client.get(format!("https://api.github.com/repos/{}", repo))
+            .header("User-Agent", "reqwest")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .send()?;
+        // GitHub answers 404 for private repositories the token can't see
+        let permissions = if response.status().is_success() {
+            let json: Value = response.json()?;
+            let permission = |name: &str| json["permissions"][name].as_bool().unwrap_or(false);
+            Some(RepoPermissions { admin: permission("admin"), push: permission("push"), pull: permission("pull") })
+        } else {
+            None
+        };
+        Some((repo, permissions))
+    };
+
+    Ok(TokenReport::new(token, &login, scopes.as_deref(), expires_at.as_deref(), repo_permissions))
+}
+
+/// Exchanges a signed GitHub App JWT for an access token of one installation of the app.
+pub fn create_installation_token(installation_id: &str, jwt: &str) -> Result<InstallationToken, Box<dyn Error>> {
+    debug!("Requesting a token for GitHub App installation: {}", installation_id);
+    let response = Client::new()
+        .post(format!("https://api.github.com/app/installations/{}/access_tokens", installation_id))
+        .header("User-Agent", "reqwest")
+        .header("Authorization", format!("Bearer {}", jwt))
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+
+    let status = response.status();
+    let json: Value = response.json()?;
+    if !status.is_success() {
+        return Err(format!("GitHub refused the installation token ({}): {}", status, json["message"].as_str().unwrap_or_default()).into());
+    }
+    let token = json["token"].as_str().ok_or("No token in GitHub's response")?;
+    let expires_at = json["expires_at"].as_str().and_then(parse_timestamp).ok_or("No valid expiry in GitHub's response")?;
+    Ok(InstallationToken { token: token.to_string(), expires_at })
+}
+
+/// Full names (`owner/repo`) of every repository in an organization the token can see.
+pub fn get_org_repos(org: &str, token: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    debug!("Fetching repositories for organization: {}", org);
+    let client = Client::new();
+
+    let mut repos = Vec::new();
+    let mut next_page_url = Some(format!("https://api.github.com/orgs/{}/repos?per_page=100", org));
+
+    while let Some(url) = next_page_url {
+        next_page_url = None;
+
+        let response = client.get(&url)
+            .header("User-Agent", "reqwest")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .send()?;
+
+        if !response.status().is_success() {
+            return Err(response.text()?.into());
+        }
+
+        let headers = response.headers().clone();
+        let json: Value = response.json()?;
+        repos.extend(json.as_array().into_iter().flatten().filter_map(|repo| repo["full_name"].as_str()).map(String::from));
+
+        if let Some(link_header) = headers.get(LINK) {
+            if let Ok(link_header_str) = link_header.to_str() {
+                next_page_url = extract_next_page_url(link_header_str);
+            }
+        }
+    }
+
+    Ok(repos)
+}
+
+/// Builds the workflow inventory of an organization on a background thread. `progress` holds
+/// (repositories done, repositories total) for the UI.
+pub fn fetch_org_inventory(shared_result: SharedInventoryResult, progress: Arc<Mutex<(usize, usize)>>, org: String, token: String) {
+    let result = get_org_repos(&org, &token).map_err(|e| e.to_string()).map(|repos| {
+        *progress.lock().unwrap() = (0, repos.len());
+        let mut inventory = Inventory { org: org.clone(), ..Default::default() };
+        for (done, repo) in repos.iter().enumerate() {
+            match get_actions(repo, &token) {
+                Ok(workflows) => {
+                    let ids: Vec<u64> = workflows.iter().map(|workflow| workflow.id).collect();
+                    let runs = get_latest_runs(repo, &token, &ids).unwrap_or_else(|e| {
+                        inventory.failed_repos.push((repo.clone(), format!("Latest runs: {}", e)));
+                        HashMap::new()
+                    });
+                    // Only the first failure per repository is recorded; the rest are usually the same
+                    let mut yaml_error = None;
+                    for workflow in workflows {
+                        let triggers = pull_workflow_yaml(repo, &token, &Some(workflow.path.clone()))
+                            .and_then(|yaml| Ok(serde_yaml::from_str::<serde_yaml::Value>(&yaml)?))
+                            .map(|yaml| Workflow::from_yaml(&yaml).triggers)
+                            .unwrap_or_else(|e| {
+                                yaml_error.get_or_insert_with(|| format!("{}: {}", workflow.path, e));
+                                Vec::new()
+                            });
+                        let last_run = runs.get(&workflow.id);
+                        inventory.rows.push(InventoryRow {
+                            repo: repo.clone(),
+                            workflow: workflow.name,
+                            path: workflow.path,
+                            state: workflow.state,
+                            triggers,
+                            last_run_status: last_run.map(|(conclusion, _)| conclusion.clone()),
+                            last_run_date: last_run.map(|(_, date)| date.clone()),
+                        });
+                    }
+                    if let Some(e) = yaml_error {
+                        inventory.failed_repos.push((repo.clone(), format!("Triggers left out, {}", e)));
+                    }
+                },
+                Err(e) => inventory.failed_repos.push((repo.clone(), e.to_string())),
+            }
+            *progress.lock().unwrap() = (done + 1, repos.len());
+        }
+        inventory
+    });
+
+    let mut shared_data = shared_result.lock().unwrap();
+    *shared_data = Some(result);
+}
+
+pub fn get_run_jobs(repo: &str, token: &str, run_id: u64) -> Result<Vec<Value>, Box<dyn Error>> {
+    debug!("Fetching jobs for run: {}", run_id);
+    let url = format!("https://api.github.com/repos/{}/actions/runs/{}/jobs?per_page=100", repo, run_id);
+
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(url)
+        .header("User-Agent", "reqwest")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(response.text()?.into());
+    }
+
+    let json: Value = response.json()?;
+    Ok(json["jobs"].as_array().cloned().unwrap_or_default())
+}
+
+
 pub fn get_repo(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result<(), Box<dyn Error>> {
-    println!("Processing repository: {}", repo_slug);
+    debug!("Processing repository: {}", repo_slug);
     let repo_url = format!("https://github.com/{}.git", repo_slug);
-    println!("Repository URL: {}", &repo_url);
+    debug!("Repository URL: {}", &repo_url);
 
     let mut cb = RemoteCallbacks::new();
     cb.credentials(move |_url, _username_from_url, _allowed_types| {
-        Cred::userpass_plaintext("dummy_username", api_key)
+        Cred::userpass_plaintext(GIT_TOKEN_USER, api_key)
     });
 
     let mut fo = FetchOptions::new();
@@ -108,7 +379,7 @@ pub fn get_repo(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result
 
         match Repository::open(&path) {
             Ok(repo) => {
-                println!("Repository already exists, fetching updates...");
+                info!("Repository already exists, fetching updates...");
                 let mut remote = repo.find_remote("origin")?;
                 // Adjust branch name here if necessary
                 remote.fetch(&["main"], Some(&mut fo), None)?;
@@ -118,7 +389,7 @@ pub fn get_repo(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result
                 Ok(())
             },
             Err(_) => {
-                println!("Repository does not exist, cloning...");
+                info!("Repository does not exist, cloning...");
                 let mut builder = RepoBuilder::new();
                 builder.fetch_options(fo);
                 match builder.clone(&*repo_url, path) {
@@ -133,13 +404,13 @@ pub fn get_repo(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result
 }
 
 pub fn get_repo_scratch(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result<(), Box<dyn Error>> {
-    println!("Processing repository: {}", repo_slug);
+    debug!("Processing repository: {}", repo_slug);
     let repo_url = format!("https://github.com/{}.git", repo_slug);
-    println!("Repository URL: {}", &repo_url);
+    debug!("Repository URL: {}", &repo_url);
 
     let mut cb = RemoteCallbacks::new();
     cb.credentials(move |_url, _username_from_url, _allowed_types| {
-        Cred::userpass_plaintext("dummy_username", api_key)
+        Cred::userpass_plaintext(GIT_TOKEN_USER, api_key)
     });
 
     let mut fo = FetchOptions::new();
@@ -148,7 +419,7 @@ pub fn get_repo_scratch(repo_slug: &str, api_key: &str, path: &Option<String>) -
     if let Some(ref path_str) = path {
         let path = Path::new(path_str);
 
-        println!("Cloning repository to ensure fresh pull...");
+        info!("Cloning repository to ensure fresh pull...");
         let mut builder = RepoBuilder::new();
         builder.fetch_options(fo);
         match builder.clone(&*repo_url, path) {
@@ -162,7 +433,7 @@ pub fn get_repo_scratch(repo_slug: &str, api_key: &str, path: &Option<String>) -
 
 
 pub fn push_repo(repo_path: &str, api_key: &str) -> Result<(), Box<dyn Error>> {
-    println!("Pushing to repository at path: {}", repo_path);
+    debug!("Pushing to repository at path: {}", repo_path);
 
     // Open the existing repository
     let repo = Repository::open(repo_path)?;
@@ -171,7 +442,7 @@ pub fn push_repo(repo_path: &str, api_key: &str) -> Result<(), Box<dyn Error>> {
     // Prepare authentication callbacks
     let mut callbacks = RemoteCallbacks::new();
     callbacks.credentials(|_url, _username_from_url, _allowed_types| {
-        Cred::userpass_plaintext("dummy_username", api_key)
+        Cred::userpass_plaintext(GIT_TOKEN_USER, api_key)
     });
 
     // Prepare push options with the callbacks
@@ -195,9 +466,8 @@ pub fn find_last_commit(repo: &Repository) -> Result<Commit<'_>, Box<dyn Error>>
 }
 
 pub fn pull_workflow_yaml(repo_slug: &str, api_key: &str, path: &Option<String>) -> Result<String, Box<dyn Error>> {
-    println!("Pulling workflow YAML for repository: {}", repo_slug);
-    println!("Using API key: {}", api_key);
-    println!("Using path: {:?}", path);
+    debug!("Pulling workflow YAML for repository: {}", repo_slug);
+    debug!("Using path: {:?}", path);
 
     if let Some(workflow_path) = path {
         let repo_url = format!("https://api.github.com/repos/{}/contents/{}", repo_slug, workflow_path);
@@ -207,7 +477,11 @@ pub fn pull_workflow_yaml(repo_slug: &str, api_key: &str, path: &Option<String>)
             .header("Authorization", format!("Bearer {}", api_key))
             .send()?;
 
-        if response.status().is_success() {
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e.into());
+        }
+        let status = response.status();
+        if status.is_success() {
             let content = response.json::<serde_json::Value>()?;
             if let Some(content_str) = content["content"].as_str() {
                 // Remove newline and other whitespace characters
@@ -219,14 +493,14 @@ pub fn pull_workflow_yaml(repo_slug: &str, api_key: &str, path: &Option<String>)
             }
         }
 
-        Err("Failed to fetch or decode workflow YAML".into())
+        Err(format!("Failed to fetch or decode {} ({})", workflow_path, status).into())
     } else {
         Err("Workflow path not provided".into())
     }
 }
 
 pub fn run_workflow(repo_slug: &str, api_key: &str, workflow_id: u64, branch: &str, inputs: Option<&HashMap<String, String>>) -> Result<(), Box<dyn Error>> {
-    println!("Triggering workflow for repository: {}", repo_slug);
+    debug!("Triggering workflow for repository: {}", repo_slug);
 
     let url = format!("https://api.github.com/repos/{}/actions/workflows/{}/dispatches", repo_slug, workflow_id);
     let client = reqwest::blocking::Client::new();
@@ -245,7 +519,7 @@ pub fn run_workflow(repo_slug: &str, api_key: &str, workflow_id: u64, branch: &s
 
     // Print the request body for debugging
     let request_body = serde_json::to_string_pretty(&body).unwrap_or_else(|_| "Failed to serialize request body".to_string());
-    println!("Request body:\n{}", request_body);
+    debug!("Request body:\n{}", request_body);
 
     let response = client.post(&url)
         .header("User-Agent", "reqwest")
@@ -0,0 +1,133 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Hand-off slot for an inventory built on a background thread.
+pub type SharedInventoryResult = Arc<Mutex<Option<Result<Inventory, String>>>>;
+
+/// One workflow of one repository in the organization inventory.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct InventoryRow {
+    pub repo: String,
+    pub workflow: String,
+    pub path: String,
+    pub state: String,
+    pub triggers: Vec<String>,
+    pub last_run_status: Option<String>,
+    pub last_run_date: Option<String>, // ISO 8601, as GitHub reports it
+}
+
+impl InventoryRow {
+    /// Whether every word of `query` appears in one of the row's columns (case-insensitive).
+    pub fn matches(&self, query: &str) -> bool {
+        let columns = [
+            self.repo.to_lowercase(),
+            self.workflow.to_lowercase(),
+            self.path.to_lowercase(),
+            self.triggers.join(" ").to_lowercase(),
+            self.last_run_status.as_deref().unwrap_or_default().to_lowercase(),
+        ];
+        query.to_lowercase()
+            .split_whitespace()
+            .all(|word| columns.iter().any(|column| column.contains(word)))
+    }
+}
+
+/// Columns the inventory table can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InventorySort {
+    #[default]
+    Repo,
+    Workflow,
+    LastRunStatus,
+    LastRunDate,
+}
+
+impl InventorySort {
+    pub const ALL: [InventorySort; 4] = [
+        InventorySort::Repo,
+        InventorySort::Workflow,
+        InventorySort::LastRunStatus,
+        InventorySort::LastRunDate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InventorySort::Repo => "Repository",
+            InventorySort::Workflow => "Workflow",
+            InventorySort::LastRunStatus => "Last run",
+            InventorySort::LastRunDate => "Date",
+        }
+    }
+}
+
+/// Workflows across every repository of an organization, for audits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    pub org: String,
+    pub rows: Vec<InventoryRow>,
+    pub failed_repos: Vec<(String, String)>, // Repositories whose workflows, runs or YAML could not (all) be read, with the error
+}
+
+impl Inventory {
+    /// Rows matching the text query and, if given, the last run status, in the chosen order.
+    /// Rows that never ran match the status "never".
+    pub fn filtered(&self, query: &str, status: Option<&str>, sort: InventorySort) -> Vec<&InventoryRow> {
+        let mut rows: Vec<&InventoryRow> = self.rows.iter()
+            .filter(|row| row.matches(query))
+            .filter(|row| status.map_or(true, |status| row.last_run_status.as_deref().unwrap_or("never") == status))
+            .collect();
+        match sort {
+            InventorySort::Repo => rows.sort_by_key(|row| (row.repo.to_lowercase(), row.workflow.to_lowercase())),
+            InventorySort::Workflow => rows.sort_by_key(|row| (row.workflow.to_lowercase(), row.repo.to_lowercase())),
+            InventorySort::LastRunStatus => rows.sort_by_key(|row| row.last_run_status.clone()),
+            // Most recent first, never-run workflows last
+            InventorySort::LastRunDate => rows.sort_by(|a, b| b.last_run_date.cmp(&a.last_run_date)),
+        }
+        rows
+    }
+
+    /// Distinct last run statuses, for the status filter.
+    pub fn statuses(&self) -> Vec<String> {
+        let mut statuses: Vec<String> = self.rows.iter()
+            .map(|row| row.last_run_status.clone().unwrap_or_else(|| "never".to_string()))
+            .collect();
+        statuses.sort();
+        statuses.dedup();
+        statuses
+    }
+}
+
+const CSV_HEADER: &str = "repo,workflow,path,state,triggers,last_run_status,last_run_date";
+
+/// CSV with a header row. Triggers are separated by spaces within their column.
+pub fn to_csv(rows: &[&InventoryRow]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for row in rows {
+        let fields = [
+            row.repo.as_str(),
+            row.workflow.as_str(),
+            row.path.as_str(),
+            row.state.as_str(),
+            &row.triggers.join(" "),
+            row.last_run_status.as_deref().unwrap_or_default(),
+            row.last_run_date.as_deref().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        let _ = writeln!(csv, "{}", line.join(","));
+    }
+    csv
+}
+
+pub fn to_json(rows: &[&InventoryRow]) -> Result<String, String> {
+    serde_json::to_string_pretty(rows).map_err(|e| format!("Failed to serialize inventory: {}", e))
+}
+
+// Quotes fields containing separators, quotes or line breaks, doubling inner quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -2,4 +2,24 @@
 
 pub mod app;
 pub use app::TemplateApp;
-mod helpers;
\ No newline at end of file
+mod helpers;
+mod workflow;
+mod editor;
+mod templates;
+mod folders;
+mod smart_folders;
+mod tags;
+mod search;
+mod shared_layout;
+mod quick_access;
+mod workspace;
+mod inventory;
+mod profiles;
+mod vault;
+mod password;
+mod secrets;
+mod logging;
+mod token_check;
+mod github_app;
+mod device_flow;
+mod config_schema;
\ No newline at end of file
@@ -0,0 +1,188 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{LevelFilter, Log, Metadata, Record};
+use regex::Regex;
+use zeroize::Zeroize;
+
+pub const LOG_DIR: &str = "logs";
+pub const LOG_FILE: &str = "actionallegro.log";
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+const KEPT_LOGS: usize = 3;
+
+/// Levels offered in the settings, quietest first.
+pub const LEVELS: [LevelFilter; 5] = [LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info, LevelFilter::Debug, LevelFilter::Trace];
+
+const MASK: &str = "[REDACTED]";
+// Shorter secrets (a short password) would mask ordinary words that happen to match
+const MIN_SECRET_LEN: usize = 8;
+
+// Secrets known at runtime (PAT, API key, password), masked wherever they show up. Each has a
+// slot so a refreshed token replaces the one before it.
+static KNOWN_SECRETS: Mutex<Vec<(&'static str, String)>> = Mutex::new(Vec::new());
+
+/// Masks `secret` in every later log line, whatever its format, replacing whatever was
+/// registered under `slot` before.
+pub fn register_secret(slot: &'static str, secret: &str) {
+    let mut secrets = KNOWN_SECRETS.lock().unwrap();
+    if let Some(index) = secrets.iter().position(|(known, _)| *known == slot) {
+        let (_, mut previous) = secrets.swap_remove(index);
+        previous.zeroize();
+    }
+    if secret.len() >= MIN_SECRET_LEN {
+        secrets.push((slot, secret.to_string()));
+    }
+}
+
+/// Wipes every registered secret, for when the app locks.
+pub fn clear_secrets() {
+    let mut secrets = KNOWN_SECRETS.lock().unwrap();
+    for (_, secret) in secrets.iter_mut() {
+        secret.zeroize();
+    }
+    secrets.clear();
+}
+
+fn patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| vec![
+        // GitHub tokens: classic, OAuth, app, refresh and fine-grained
+        (Regex::new(r"\b(?:gh[pousr]_[A-Za-z0-9]{16,}|github_pat_[A-Za-z0-9_]{16,})").unwrap(), MASK),
+        // Authorization headers; "token" alone is too common in prose to mask what follows it
+        (Regex::new(r"(?i)\b(bearer)(\s+)[A-Za-z0-9._~+/=-]{8,}").unwrap(), "$1$2[REDACTED]"),
+        // Credentials embedded in URLs
+        (Regex::new(r"://[^/@\s]+@").unwrap(), "://[REDACTED]@"),
+        (Regex::new(r#"(?i)\b(password|passwd|secret|api[_ ]?key|pat|token|key|iv)("?\s*[:=]\s*"?)[^\s",}]+"#).unwrap(), "$1$2[REDACTED]"),
+    ])
+}
+
+/// Masks tokens, keys and passwords in a log message.
+pub fn redact(message: &str) -> String {
+    let mut redacted = message.to_string();
+    for (_, secret) in KNOWN_SECRETS.lock().unwrap().iter() {
+        redacted = redacted.replace(secret.as_str(), MASK);
+    }
+    for (pattern, replacement) in patterns() {
+        redacted = pattern.replace_all(&redacted, *replacement).into_owned();
+    }
+    redacted
+}
+
+/// Appends to a log file, moving it to `<name>.1` once it grows past `max_bytes`.
+/// Older files shift up to `<name>.<keep>`; anything beyond that is dropped.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Option<File>,
+}
+
+impl RotatingFile {
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        RotatingFile { path, max_bytes, keep, file: None }
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let size = fs::metadata(&self.path).map(|meta| meta.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.file = None;
+            self.rotate()?;
+        }
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.file = Some(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+        let file = self.file.as_mut().expect("log file was just opened");
+        writeln!(file, "{}", line)
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        for index in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+pub fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Where the log file lives inside the config directory.
+pub fn log_file(config_dir: &str) -> PathBuf {
+    Path::new(config_dir).join(LOG_DIR).join(LOG_FILE)
+}
+
+struct AppLogger {
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Dependencies (eframe, reqwest, ...) are only interesting when something goes wrong
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with(env!("CARGO_CRATE_NAME")) || metadata.level() <= log::Level::Warn)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!("{} {:<5} {}: {}", timestamp(), record.level(), record.target(), redact(&record.args().to_string()));
+        eprintln!("{}", line);
+        if let Some(file) = &self.file {
+            // Logging must never take the app down
+            let _ = file.lock().unwrap().write_line(&line);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the redacting logger, writing to stderr and, if given, a rotating file in the config
+/// directory. `RUST_LOG` set to a plain level overrides `level`.
+pub fn init(config_dir: Option<&str>, level: LevelFilter) {
+    let file = config_dir.map(|dir| Mutex::new(RotatingFile::new(log_file(dir), MAX_LOG_BYTES, KEPT_LOGS)));
+    if log::set_boxed_logger(Box::new(AppLogger { file })).is_ok() {
+        set_level(level);
+    }
+}
+
+pub fn set_level(level: LevelFilter) {
+    let level = std::env::var("RUST_LOG").ok().and_then(|env| env.parse().ok()).unwrap_or(level);
+    log::set_max_level(level);
+}
+
+/// Parses a level saved in the config, falling back to `Info`.
+pub fn parse_level(level: &str) -> LevelFilter {
+    level.parse().unwrap_or(LevelFilter::Info)
+}
+
+// UTC time as `YYYY-MM-DDTHH:MM:SSZ`
+fn timestamp() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, rest / 3_600, rest % 3_600 / 60, rest % 60)
+}
@@ -1,6 +1,26 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 mod helpers;
+mod workflow;
+mod editor;
+mod templates;
+mod folders;
+mod smart_folders;
+mod tags;
+mod search;
+mod shared_layout;
+mod quick_access;
+mod workspace;
+mod inventory;
+mod profiles;
+mod vault;
+mod password;
+mod secrets;
+mod logging;
+mod token_check;
+mod github_app;
+mod device_flow;
+mod config_schema;
 
 // main.rs or lib.rs
 #[cfg(test)]
@@ -10,7 +30,7 @@ mod tests;
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    // Logging is set up by the app once it knows the config directory (`RUST_LOG=debug` still works).
 
 
     let native_options = eframe::NativeOptions {
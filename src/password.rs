@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Failed unlock attempts allowed before the backoff starts.
+pub const FREE_ATTEMPTS: u32 = 3;
+const MAX_BACKOFF_SECS: u64 = 300;
+
+/// Hashes the unlock password with Argon2id into a PHC string (`$argon2id$v=19$m=...`),
+/// which carries its own salt and cost parameters.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    hash_password_with(password, Params::default())
+}
+
+pub fn hash_password_with(password: &str, params: Params) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash password: {}", e))
+}
+
+/// Hashes written before PHC strings: hex SHA-256 of salt plus password.
+pub fn is_legacy_hash(stored: &str) -> bool {
+    !stored.starts_with('$')
+}
+
+/// Checks `attempt` against a stored hash in constant time. Legacy hashes need the separate salt
+/// they were stored with; callers should replace them with `hash_password` after a match.
+pub fn verify_password(stored: &str, legacy_salt: Option<&str>, attempt: &str) -> bool {
+    if is_legacy_hash(stored) {
+        let Some(salt) = legacy_salt else { return false };
+        let hex = format!("{:x}", Sha256::new().chain_update(salt).chain_update(attempt).finalize());
+        return hex.as_bytes().ct_eq(stored.as_bytes()).into();
+    }
+    match PasswordHash::new(stored) {
+        // The verifier compares the derived hash in constant time
+        Ok(hash) => Argon2::default().verify_password(attempt.as_bytes(), &hash).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Backoff for the unlock prompt: after `FREE_ATTEMPTS` failures each further failure locks the
+/// prompt for twice as long as the previous one, up to five minutes.
+#[derive(Debug, Clone, Default)]
+pub struct UnlockThrottle {
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+impl UnlockThrottle {
+    /// How long the prompt stays locked, if it is.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.locked_until.and_then(|until| until.checked_duration_since(now)).filter(|left| !left.is_zero())
+    }
+
+    /// Records a failed attempt and returns the lockout it caused, if any.
+    pub fn record_failure(&mut self, now: Instant) -> Option<Duration> {
+        self.failures += 1;
+        if self.failures < FREE_ATTEMPTS {
+            return None;
+        }
+        let exponent = (self.failures - FREE_ATTEMPTS).min(16);
+        let backoff = Duration::from_secs((1u64 << exponent).min(MAX_BACKOFF_SECS));
+        self.locked_until = Some(now + backoff);
+        Some(backoff)
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn reset(&mut self) {
+        *self = UnlockThrottle::default();
+    }
+
+    /// The throttle as it's stored in the config; `now_secs` is `now` in seconds since the epoch.
+    pub fn save(&self, now: Instant, now_secs: u64) -> SavedThrottle {
+        let locked_until = self.remaining(now).map(|left| now_secs + left.as_secs() + u64::from(left.subsec_nanos() > 0));
+        SavedThrottle { failures: self.failures, locked_until }
+    }
+
+    /// Picks up where the saved throttle left off. A deadline further out than the longest
+    /// backoff (a clock that was set back) is cut to it.
+    pub fn restore(saved: &SavedThrottle, now: Instant, now_secs: u64) -> Self {
+        let locked_until = saved.locked_until
+            .and_then(|until| until.checked_sub(now_secs))
+            .filter(|left| *left > 0)
+            .map(|left| now + Duration::from_secs(left.min(MAX_BACKOFF_SECS)));
+        UnlockThrottle { failures: saved.failures, locked_until }
+    }
+}
+
+/// Failed unlock attempts saved with the config so restarting the app doesn't reset the backoff.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SavedThrottle {
+    pub failures: u32,
+    pub locked_until: Option<u64>, // Seconds since the epoch
+}
+
+/// Minutes without input before the app locks itself, unless the config says otherwise.
+pub const DEFAULT_AUTO_LOCK_MINUTES: u32 = 15;
+
+/// Tracks when the user last touched the app so it can lock itself when left unattended.
+#[derive(Debug, Clone)]
+pub struct IdleTimer {
+    last_activity: Instant,
+}
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        IdleTimer { last_activity: Instant::now() }
+    }
+}
+
+impl IdleTimer {
+    pub fn touch(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Time left before locking, `None` when auto-lock is off (`minutes` is zero).
+    pub fn remaining(&self, minutes: u32, now: Instant) -> Option<Duration> {
+        if minutes == 0 {
+            return None;
+        }
+        let timeout = Duration::from_secs(u64::from(minutes) * 60);
+        Some(timeout.saturating_sub(now.saturating_duration_since(self.last_activity)))
+    }
+
+    pub fn is_expired(&self, minutes: u32, now: Instant) -> bool {
+        self.remaining(minutes, now).map_or(false, |left| left.is_zero())
+    }
+}
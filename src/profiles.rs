@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::config_schema::save_config;
+use crate::password::verify_password;
+use crate::secrets::{SecretStore, Secrets, load_secrets, save_secrets};
+use crate::vault::{Vault, KdfParams, derive_key, new_salt};
+
+/// The profile stored in the original `config.json`, so existing setups keep working.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+
+/// Where a profile's configuration lives inside the config directory.
+pub fn config_file(config_dir: &str, profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        Path::new(config_dir).join("config.json")
+    } else {
+        Path::new(config_dir).join(PROFILES_DIR).join(format!("{}.json", profile))
+    }
+}
+
+/// Every profile with a configuration file, the default one first.
+pub fn list_profiles(config_dir: &str) -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(Path::new(config_dir).join(PROFILES_DIR))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .filter(|name| name != DEFAULT_PROFILE)
+        .collect();
+    profiles.sort_by_key(|name| name.to_lowercase());
+    if config_file(config_dir, DEFAULT_PROFILE).exists() {
+        profiles.insert(0, DEFAULT_PROFILE.to_string());
+    }
+    profiles
+}
+
+/// Checks a name for a new profile; names become file names, so only a safe set of characters is allowed.
+pub fn validate_profile_name(config_dir: &str, name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profiles need a name".to_string());
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ') {
+        return Err(format!("'{}' may only contain letters, digits, spaces, '-' and '_'", name));
+    }
+    if list_profiles(config_dir).iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+        return Err(format!("A profile named '{}' already exists", name));
+    }
+    Ok(name.to_string())
+}
+
+/// The profile used last, falling back to the default one.
+pub fn load_active_profile(config_dir: &str) -> String {
+    fs::read_to_string(Path::new(config_dir).join(ACTIVE_PROFILE_FILE))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty() && config_file(config_dir, name).exists())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+pub fn save_active_profile(config_dir: &str, profile: &str) -> Result<(), String> {
+    fs::write(Path::new(config_dir).join(ACTIVE_PROFILE_FILE), profile)
+        .map_err(|e| format!("Failed to remember the active profile: {}", e))
+}
+
+/// Copies an exported profile into the config directory under the name of its file.
+/// The caller checks that the file really is a configuration before importing it.
+pub fn import_profile(config_dir: &str, source: &Path) -> Result<String, String> {
+    let stem = source.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = validate_profile_name(config_dir, &stem)?;
+    let target = config_file(config_dir, &name);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::copy(source, &target).map_err(|e| format!("Failed to import {}: {}", source.display(), e))?;
+    Ok(name)
+}
+
+/// Writes a copy of a profile's configuration to `target` with its secrets still encrypted.
+/// Profiles that keep their secrets in the OS keyring go through `export_keyring_profile`.
+pub fn export_profile(config_dir: &str, profile: &str, target: &Path) -> Result<(), String> {
+    fs::copy(config_file(config_dir, profile), target)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to export profile '{}': {}", profile, e))
+}
+
+// The config file fields holding the sealed PAT, listener API key and GitHub App key
+const VAULT_FIELDS: [&str; 3] = ["vault", "action_api_key_vault", "github_app_key_vault"];
+
+fn read_config(path: &Path) -> Result<Value, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Exports a profile whose secrets live in the OS keyring, sealing them into the copy's file vaults
+/// under `password` so the profile still works on a machine without those keyring entries. The
+/// password has to be the profile's own, since that's what the copy is unlocked with.
+pub fn export_keyring_profile(config_dir: &str, profile: &str, target: &Path, store: &dyn SecretStore, password: &str) -> Result<(), String> {
+    let mut config = read_config(&config_file(config_dir, profile))?;
+    let hash = config.get("hashed_password").and_then(Value::as_str).unwrap_or_default();
+    let legacy_salt = config.get("salt").and_then(Value::as_str);
+    if !verify_password(hash, legacy_salt, password) {
+        return Err(format!("Profile '{}' uses a different password; switch to it to export it with its secrets", profile));
+    }
+    let secrets = load_secrets(store, profile)?;
+    let (salt, kdf) = (new_salt(), KdfParams::default());
+    let key = derive_key(password, &salt, &kdf)?;
+    let Some(fields) = config.as_object_mut() else {
+        return Err(format!("Profile '{}' does not contain a configuration object", profile));
+    };
+    for (field, secret) in VAULT_FIELDS.into_iter().zip([&secrets.github_pat, &secrets.action_api_key, &secrets.github_app_key]) {
+        let sealed = Vault::seal(&key, &salt, &kdf, secret.as_bytes())?;
+        fields.insert(field.to_string(), serde_json::to_value(sealed).map_err(|e| e.to_string())?);
+    }
+    fields.insert("secret_backend".to_string(), Value::from("FileVault"));
+    fs::write(target, config.to_string()).map_err(|e| format!("Failed to export profile '{}': {}", profile, e))
+}
+
+/// Moves the sealed secrets of an imported profile into `store` when `password` opens them, so it
+/// ends up like the profiles created here. Returns false when there is nothing sealed or another
+/// password protects it; those keep the file vault until the profile is unlocked and saved.
+pub fn refile_imported_secrets(config_dir: &str, profile: &str, store: &dyn SecretStore, password: &str) -> Result<bool, String> {
+    let path = config_file(config_dir, profile);
+    let mut config = read_config(&path)?;
+    let vaults: Vec<Option<Vault>> = VAULT_FIELDS.iter()
+        .map(|field| config.get(*field).cloned().and_then(|vault| serde_json::from_value(vault).ok()))
+        .collect();
+    let Some(Ok((key, pat))) = vaults[0].as_ref().map(|vault| vault.unlock(password)) else { return Ok(false) };
+    let open = |vault: &Option<Vault>| -> Result<String, String> {
+        Ok(vault.as_ref().map(|vault| vault.open(&key)).transpose()?.map(|secret| String::from_utf8_lossy(&secret).into_owned()).unwrap_or_default())
+    };
+    let secrets = Secrets {
+        github_pat: String::from_utf8_lossy(&pat).into_owned(),
+        action_api_key: open(&vaults[1])?,
+        github_app_key: open(&vaults[2])?,
+    };
+    save_secrets(store, profile, &secrets)?;
+    let Some(fields) = config.as_object_mut() else { return Ok(false) };
+    for field in VAULT_FIELDS {
+        fields.insert(field.to_string(), Value::Null);
+    }
+    fields.insert("secret_backend".to_string(), Value::from("Keyring"));
+    save_config(&path, &config.to_string())?;
+    Ok(true)
+}
+
+pub fn delete_profile(config_dir: &str, profile: &str) -> Result<(), String> {
+    if profile == DEFAULT_PROFILE {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    fs::remove_file(config_file(config_dir, profile)).map_err(|e| format!("Failed to delete profile '{}': {}", profile, e))
+}
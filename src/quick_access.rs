@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::folders::WorkflowRef;
+
+/// How many workflows the Recent list remembers.
+pub const RECENT_LIMIT: usize = 10;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Favorite {
+    pub workflow_id: u64,
+    pub name: String,
+}
+
+/// Branch and inputs of the last dispatch, kept so it can be run again as is.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Dispatch {
+    pub branch: String,
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct RecentEntry {
+    pub workflow_id: u64,
+    pub name: String,
+    pub last_used: u64, // Unix seconds
+    #[serde(default)]
+    pub dispatch: Option<Dispatch>,
+}
+
+/// Pinned favorites and the most recently opened or dispatched workflows, newest first.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct QuickAccess {
+    #[serde(default)]
+    pub favorites: Vec<Favorite>,
+    #[serde(default)]
+    pub recent: Vec<RecentEntry>,
+}
+
+impl QuickAccess {
+    pub fn is_favorite(&self, workflow_id: u64) -> bool {
+        self.favorites.iter().any(|f| f.workflow_id == workflow_id)
+    }
+
+    pub fn toggle_favorite(&mut self, workflow_id: u64, name: &str) {
+        if self.is_favorite(workflow_id) {
+            self.favorites.retain(|f| f.workflow_id != workflow_id);
+        } else {
+            self.favorites.push(Favorite { workflow_id, name: name.to_string() });
+        }
+    }
+
+    /// Moves a workflow to the top of the Recent list, keeping its last dispatch.
+    pub fn record_opened(&mut self, workflow_id: u64, name: &str, now: u64) {
+        let dispatch = self.take_recent(workflow_id).and_then(|entry| entry.dispatch);
+        self.push_recent(RecentEntry { workflow_id, name: name.to_string(), last_used: now, dispatch });
+    }
+
+    pub fn record_dispatch(&mut self, workflow_id: u64, name: &str, branch: &str, inputs: &HashMap<String, String>, now: u64) {
+        self.take_recent(workflow_id);
+        let dispatch = Dispatch { branch: branch.to_string(), inputs: inputs.iter().map(|(k, v)| (k.clone(), v.clone())).collect() };
+        self.push_recent(RecentEntry { workflow_id, name: name.to_string(), last_used: now, dispatch: Some(dispatch) });
+    }
+
+    fn take_recent(&mut self, workflow_id: u64) -> Option<RecentEntry> {
+        let index = self.recent.iter().position(|entry| entry.workflow_id == workflow_id)?;
+        Some(self.recent.remove(index))
+    }
+
+    fn push_recent(&mut self, entry: RecentEntry) {
+        self.recent.insert(0, entry);
+        self.recent.truncate(RECENT_LIMIT);
+    }
+
+    /// Drops deleted workflows and picks up renames after a fetch.
+    pub fn sync_with(&mut self, actions: &[WorkflowRef]) {
+        let names: HashMap<u64, &str> = actions.iter().map(|a| (a.id, a.name.as_str())).collect();
+        self.favorites.retain_mut(|f| match names.get(&f.workflow_id) {
+            Some(name) => {
+                f.name = name.to_string();
+                true
+            },
+            None => false,
+        });
+        self.recent.retain_mut(|entry| match names.get(&entry.workflow_id) {
+            Some(name) => {
+                entry.name = name.to_string();
+                true
+            },
+            None => false,
+        });
+    }
+}
+
+/// "just now", "5m ago", "3h ago" or "2d ago".
+pub fn describe_age(then: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(then);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
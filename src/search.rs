@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use crate::folders::WorkflowRef;
+use crate::workflow::Workflow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Name,
+    Path,
+    Trigger,
+    Input,
+    Job,
+    Uses,
+    Runner,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 7] = [
+        SearchField::Name,
+        SearchField::Path,
+        SearchField::Trigger,
+        SearchField::Input,
+        SearchField::Job,
+        SearchField::Uses,
+        SearchField::Runner,
+    ];
+
+    /// Qualifier used in queries, e.g. `uses:actions/checkout`.
+    pub fn qualifier(&self) -> &'static str {
+        match self {
+            SearchField::Name => "name",
+            SearchField::Path => "path",
+            SearchField::Trigger => "trigger",
+            SearchField::Input => "input",
+            SearchField::Job => "job",
+            SearchField::Uses => "uses",
+            SearchField::Runner => "runner",
+        }
+    }
+
+    fn from_qualifier(qualifier: &str) -> Option<Self> {
+        match qualifier.to_lowercase().as_str() {
+            "on" => Some(SearchField::Trigger),
+            "runs-on" => Some(SearchField::Runner),
+            other => SearchField::ALL.into_iter().find(|field| field.qualifier() == other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryTerm {
+    pub field: Option<SearchField>,
+    pub text: String,
+}
+
+/// Splits a query into terms. `field:value` restricts a term to one field and double quotes
+/// keep spaces inside a term. Unknown qualifiers are searched as plain text.
+pub fn parse_query(query: &str) -> Vec<QueryTerm> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            },
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.into_iter()
+        .map(|token| match token.split_once(':') {
+            Some((qualifier, value)) if !value.is_empty() => match SearchField::from_qualifier(qualifier) {
+                Some(field) => QueryTerm { field: Some(field), text: value.to_lowercase() },
+                None => QueryTerm { field: None, text: token.to_lowercase() },
+            },
+            _ => QueryTerm { field: None, text: token.to_lowercase() },
+        })
+        .collect()
+}
+
+/// Everything searchable about one workflow, lowercased.
+#[derive(Debug, Clone, Default)]
+struct SearchDoc {
+    workflow: WorkflowRef,
+    fields: Vec<(SearchField, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    pub workflow: &'a WorkflowRef,
+    pub score: u32,
+    pub matched: Vec<SearchField>,
+}
+
+/// Search index over every fetched workflow. YAML details (triggers, inputs, jobs, `uses:`
+/// and runners) come from the parsed workflows, so ones that couldn't be read are only found by name and path.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    docs: Vec<SearchDoc>,
+}
+
+impl SearchIndex {
+    pub fn build(workflows: &[WorkflowRef], parsed: &HashMap<String, Workflow>) -> Self {
+        let docs = workflows.iter()
+            .map(|workflow| {
+                let mut fields = vec![
+                    (SearchField::Name, workflow.name.to_lowercase()),
+                    (SearchField::Path, workflow.path.to_lowercase()),
+                ];
+                if let Some(parsed) = parsed.get(&workflow.path) {
+                    fields.extend(parsed.triggers.iter().map(|t| (SearchField::Trigger, t.to_lowercase())));
+                    fields.extend(parsed.dispatch_inputs.iter().map(|i| (SearchField::Input, i.to_lowercase())));
+                    if let Some(contract) = &parsed.call_contract {
+                        fields.extend(contract.inputs.iter().map(|i| (SearchField::Input, i.name.to_lowercase())));
+                    }
+                    for job in &parsed.jobs {
+                        fields.push((SearchField::Job, job.id.to_lowercase()));
+                        if let Some(name) = &job.name {
+                            fields.push((SearchField::Job, name.to_lowercase()));
+                        }
+                        fields.extend(job.uses.iter().chain(&job.step_uses).map(|u| (SearchField::Uses, u.to_lowercase())));
+                        if let Some(runs_on) = &job.runs_on {
+                            fields.extend(runs_on.split(", ").map(|label| (SearchField::Runner, label.to_lowercase())));
+                        }
+                    }
+                }
+                SearchDoc { workflow: workflow.clone(), fields }
+            })
+            .collect();
+        SearchIndex { docs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Workflows matching every term, best first. Each term scores its best matching field.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let terms = parse_query(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<SearchHit<'_>> = self.docs.iter()
+            .filter_map(|doc| {
+                let mut score = 0;
+                let mut matched = Vec::new();
+                for term in &terms {
+                    let (field, term_score) = doc.fields.iter()
+                        .filter(|(field, _)| term.field.map_or(true, |wanted| wanted == *field))
+                        .filter_map(|(field, value)| match_score(&term.text, value).map(|s| (*field, s)))
+                        .filter(|(_, s)| term.field.is_none() || *s >= MIN_QUALIFIED_SCORE)
+                        .max_by_key(|(_, s)| *s)?;
+                    score += term_score;
+                    if !matched.contains(&field) {
+                        matched.push(field);
+                    }
+                }
+                Some(SearchHit { workflow: &doc.workflow, score, matched })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.workflow.name.to_lowercase().cmp(&b.workflow.name.to_lowercase())));
+        hits
+    }
+}
+
+// Qualified terms name the field they want, so characters merely appearing in order are too loose there
+const MIN_QUALIFIED_SCORE: u32 = 40;
+// Fewer than this and scattered characters match nearly anything long enough
+const MIN_SUBSEQUENCE_SCORE: u32 = 20;
+
+/// How well `term` matches `value` (both lowercase): exact beats prefix beats substring,
+/// then in-order characters (`chkout`) and single typos in a word (`chekout`).
+pub fn match_score(term: &str, value: &str) -> Option<u32> {
+    if value == term {
+        return Some(100);
+    }
+    if value.starts_with(term) {
+        return Some(80);
+    }
+    if value.contains(term) {
+        return Some(60);
+    }
+
+    let term_len = term.chars().count();
+    if term_len < 3 {
+        return None;
+    }
+    let max_typos = if term_len >= 8 { 2 } else { 1 };
+    let typo_match = value.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .any(|word| edit_distance(term, word) <= max_typos);
+    if typo_match {
+        return Some(40);
+    }
+    subsequence_score(term, value)
+}
+
+// Characters of `term` in order inside `value`; the fewer gaps the better
+fn subsequence_score(term: &str, value: &str) -> Option<u32> {
+    let mut gaps = 0;
+    let mut last_match: Option<usize> = None;
+    let mut value_chars = value.chars().enumerate();
+    for t in term.chars() {
+        let (i, _) = value_chars.find(|(_, v)| *v == t)?;
+        if last_match.map_or(false, |last| i > last + 1) {
+            gaps += 1;
+        }
+        last_match = Some(i);
+    }
+    let score = 30u32.saturating_sub(gaps * 5);
+    (score >= MIN_SUBSEQUENCE_SCORE).then_some(score)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
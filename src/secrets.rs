@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Service the app's entries are filed under in the OS keyring.
+pub const KEYRING_SERVICE: &str = "ActionAllegro";
+
+const GITHUB_PAT: &str = "github_pat";
+const ACTION_API_KEY: &str = "action_api_key";
+const GITHUB_APP_KEY: &str = "github_app_key";
+
+/// Where a profile keeps its secrets. Configs written before the keyring use the encrypted file vault.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretBackend {
+    #[default]
+    FileVault,
+    Keyring,
+}
+
+impl SecretBackend {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SecretBackend::FileVault => "encrypted config file",
+            SecretBackend::Keyring => "OS keyring",
+        }
+    }
+}
+
+/// Somewhere to keep secrets outside the config file, addressed by account name.
+pub trait SecretStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String>;
+    fn set(&self, account: &str, secret: &str) -> Result<(), String>;
+    fn delete(&self, account: &str) -> Result<(), String>;
+}
+
+/// The OS keyring: Secret Service on Linux, Keychain on macOS and Credential Manager on Windows.
+pub struct KeyringStore;
+
+impl SecretStore for KeyringStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        match keyring::Entry::new(KEYRING_SERVICE, account).and_then(|entry| entry.get_password()) {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read {} from the keyring: {}", account, e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        keyring::Entry::new(KEYRING_SERVICE, account)
+            .and_then(|entry| entry.set_password(secret))
+            .map_err(|e| format!("Failed to store {} in the keyring: {}", account, e))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        match keyring::Entry::new(KEYRING_SERVICE, account).and_then(|entry| entry.delete_password()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove {} from the keyring: {}", account, e)),
+        }
+    }
+}
+
+/// Keeps secrets in memory only, for tests. An unavailable store fails every call,
+/// like a keyring whose daemon is not running.
+#[derive(Default)]
+pub struct MemoryStore {
+    secrets: Mutex<HashMap<String, String>>,
+    unavailable: bool,
+}
+
+impl MemoryStore {
+    #[cfg(test)]
+    #[allow(dead_code)] // The tests live in the binary, so the library's test build never calls it
+    pub fn unavailable() -> Self {
+        MemoryStore { unavailable: true, ..MemoryStore::default() }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.unavailable {
+            Err("Secret store is not available".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl SecretStore for MemoryStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        self.check()?;
+        Ok(self.secrets.lock().unwrap().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        self.check()?;
+        self.secrets.lock().unwrap().insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        self.check()?;
+        self.secrets.lock().unwrap().remove(account);
+        Ok(())
+    }
+}
+
+/// The secrets a profile needs, decrypted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Secrets {
+    pub github_pat: String,
+    pub action_api_key: String,
+    pub github_app_key: String, // PEM private key of the profile's GitHub App, if it uses one
+}
+
+// Each profile gets its own entries so profiles with different tokens don't overwrite each other
+fn account(profile: &str, secret: &str) -> String {
+    format!("{}/{}", profile, secret)
+}
+
+/// Stores every secret of a profile. An error means the store can't be used and the caller
+/// should keep the secrets in the file vault instead; whatever was already written is removed
+/// again so the store never holds a stale part of the profile.
+pub fn save_secrets(store: &dyn SecretStore, profile: &str, secrets: &Secrets) -> Result<(), String> {
+    let saved = store.set(&account(profile, GITHUB_PAT), &secrets.github_pat)
+        .and_then(|()| store.set(&account(profile, ACTION_API_KEY), &secrets.action_api_key))
+        .and_then(|()| store.set(&account(profile, GITHUB_APP_KEY), &secrets.github_app_key));
+    if saved.is_err() {
+        for secret in [GITHUB_PAT, ACTION_API_KEY, GITHUB_APP_KEY] {
+            let _ = store.delete(&account(profile, secret));
+        }
+    }
+    saved
+}
+
+/// Reads a profile's secrets; ones that were never stored come back empty.
+pub fn load_secrets(store: &dyn SecretStore, profile: &str) -> Result<Secrets, String> {
+    Ok(Secrets {
+        github_pat: store.get(&account(profile, GITHUB_PAT))?.unwrap_or_default(),
+        action_api_key: store.get(&account(profile, ACTION_API_KEY))?.unwrap_or_default(),
+        github_app_key: store.get(&account(profile, GITHUB_APP_KEY))?.unwrap_or_default(),
+    })
+}
+
+pub fn delete_secrets(store: &dyn SecretStore, profile: &str) -> Result<(), String> {
+    store.delete(&account(profile, GITHUB_PAT))?;
+    store.delete(&account(profile, ACTION_API_KEY))?;
+    store.delete(&account(profile, GITHUB_APP_KEY))
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::folders::{FolderNode, WorkflowRef};
+use crate::smart_folders::SmartFolder;
+use crate::tags::TagStore;
+
+/// Where the team's shared organization lives, relative to the repository root.
+pub const LAYOUT_FILE: &str = ".github/actionallegro.yml";
+
+const LAYOUT_VERSION: u32 = 1;
+
+/// Folders, tags and smart folders committed to the repository so a whole team shares them.
+/// Workflows are referenced by file path, which stays readable in review and is the same
+/// for everyone.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedLayout {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<SharedFolder>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<SharedTag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub smart_folders: Vec<SmartFolder>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedFolder {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workflows: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SharedFolder>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedTag {
+    pub name: String,
+    pub color: [u8; 3],
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workflows: Vec<String>,
+}
+
+impl SharedLayout {
+    /// Reads the layout file from a local clone. A missing file is not an error.
+    pub fn load(repo_path: &str) -> Result<Option<Self>, String> {
+        let file_path = Path::new(repo_path).join(LAYOUT_FILE);
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&file_path).map_err(|e| format!("Failed to read {}: {}", LAYOUT_FILE, e))?;
+        let layout: SharedLayout = serde_yaml::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", LAYOUT_FILE, e))?;
+        if layout.version > LAYOUT_VERSION {
+            return Err(format!("{} was written by a newer version of ActionAllegro (layout version {})", LAYOUT_FILE, layout.version));
+        }
+        Ok(Some(layout))
+    }
+
+    /// Writes the layout into the clone, ready to be committed from the Pull and Upload tab.
+    pub fn save(&self, repo_path: &str) -> Result<(), String> {
+        let file_path = Path::new(repo_path).join(LAYOUT_FILE);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let yaml = serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize layout: {}", e))?;
+        let contents = format!("# Shared workflow organization for ActionAllegro. Edit in the app or by hand.\n{}", yaml);
+        fs::write(file_path, contents).map_err(|e| format!("Failed to write {}: {}", LAYOUT_FILE, e))
+    }
+
+    /// Captures the current organization. Workflows that were never fetched (no path yet) are left out.
+    pub fn from_app(tree: &FolderNode, tags: &TagStore, smart_folders: &[SmartFolder], actions: &[WorkflowRef]) -> Self {
+        fn folder(node: &FolderNode) -> SharedFolder {
+            SharedFolder {
+                name: node.name.clone(),
+                workflows: node.workflows.iter().filter(|w| !w.path.is_empty()).map(|w| w.path.clone()).collect(),
+                children: node.children.iter().map(folder).collect(),
+            }
+        }
+
+        let paths: HashMap<u64, &str> = actions.iter().map(|a| (a.id, a.path.as_str())).collect();
+        let shared_tags = tags.tags.iter()
+            .map(|tag| {
+                let mut workflows: Vec<String> = tags.assignments.iter()
+                    .filter(|(_, names)| names.contains(&tag.name))
+                    .filter_map(|(id, _)| paths.get(id).map(|path| path.to_string()))
+                    .collect();
+                workflows.sort();
+                SharedTag { name: tag.name.clone(), color: tag.color, workflows }
+            })
+            .collect();
+
+        SharedLayout {
+            version: LAYOUT_VERSION,
+            folders: tree.children.iter().map(folder).collect(),
+            tags: shared_tags,
+            smart_folders: smart_folders.to_vec(),
+        }
+    }
+
+    /// Merges the shared layout into the personal one. Shared folders, tags and smart folders
+    /// are added where missing; a workflow is only moved into a shared folder while it is still
+    /// unfiled, so personal filing always wins. Returns how many workflows were filed.
+    pub fn merge_into(&self, tree: &mut FolderNode, tags: &mut TagStore, smart_folders: &mut Vec<SmartFolder>, actions: &[WorkflowRef]) -> usize {
+        fn merge_folder(shared: &SharedFolder, parent: &[String], tree: &mut FolderNode, ids: &HashMap<&str, u64>, filed: &mut usize) {
+            let mut path = parent.to_vec();
+            path.push(shared.name.clone());
+            if tree.get(&path).is_none() && tree.add_folder(parent, &shared.name).is_err() {
+                return;
+            }
+            for workflow_path in &shared.workflows {
+                let Some(&id) = ids.get(workflow_path.as_str()) else { continue };
+                if tree.workflows.iter().any(|w| w.id == id) {
+                    tree.move_workflow(id, &path);
+                    *filed += 1;
+                }
+            }
+            for child in &shared.children {
+                merge_folder(child, &path, tree, ids, filed);
+            }
+        }
+
+        let ids: HashMap<&str, u64> = actions.iter().map(|a| (a.path.as_str(), a.id)).collect();
+        let mut filed = 0;
+        for folder in &self.folders {
+            merge_folder(folder, &[], tree, &ids, &mut filed);
+        }
+
+        for tag in &self.tags {
+            if tags.get(&tag.name).is_none() {
+                let _ = tags.define(&tag.name, tag.color);
+            }
+            tags.tag(tag.workflows.iter().filter_map(|path| ids.get(path.as_str()).copied()), &tag.name);
+        }
+
+        for smart_folder in &self.smart_folders {
+            if !smart_folders.iter().any(|existing| existing.name == smart_folder.name) {
+                smart_folders.push(smart_folder.clone());
+            }
+        }
+        filed
+    }
+}
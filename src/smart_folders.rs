@@ -0,0 +1,184 @@
+use std::cell::OnceCell;
+use regex::{Regex, RegexBuilder};
+use crate::folders::WorkflowRef;
+
+/// What a workflow looks like to the smart folder rules. Rebuilt after every fetch from
+/// the API listing, the workflow YAML (local clone or GitHub) and the latest runs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkflowMeta {
+    pub workflow: WorkflowRef,
+    pub triggers: Vec<String>,
+    pub inputs: Vec<String>,
+    pub last_conclusion: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    NameGlob,
+    NameRegex,
+    PathPrefix,
+    Trigger,
+    HasInput,
+    LastConclusion,
+    State,
+}
+
+impl RuleKind {
+    pub const ALL: [RuleKind; 7] = [
+        RuleKind::NameGlob,
+        RuleKind::NameRegex,
+        RuleKind::PathPrefix,
+        RuleKind::Trigger,
+        RuleKind::HasInput,
+        RuleKind::LastConclusion,
+        RuleKind::State,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleKind::NameGlob => "Name matches glob",
+            RuleKind::NameRegex => "Name matches regex",
+            RuleKind::PathPrefix => "Path starts with",
+            RuleKind::Trigger => "Has trigger",
+            RuleKind::HasInput => "Has input",
+            RuleKind::LastConclusion => "Last run conclusion",
+            RuleKind::State => "State is",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            RuleKind::NameGlob => "deploy-*",
+            RuleKind::NameRegex => "^(build|test)",
+            RuleKind::PathPrefix => ".github/workflows/release",
+            RuleKind::Trigger => "workflow_dispatch",
+            RuleKind::HasInput => "environment",
+            RuleKind::LastConclusion => "failure",
+            RuleKind::State => "active or disabled",
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct SmartRule {
+    pub kind: RuleKind,
+    pub value: String,
+    // `NameRegex` pattern compiled on first use; None when it doesn't compile
+    #[serde(skip)]
+    regex: OnceCell<Option<Regex>>,
+}
+
+impl PartialEq for SmartRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.value == other.value
+    }
+}
+
+impl SmartRule {
+    pub fn new(kind: RuleKind, value: &str) -> Self {
+        SmartRule { kind, value: value.to_string(), regex: OnceCell::new() }
+    }
+
+    /// Drops the compiled pattern. Call it after changing `kind` or `value`.
+    pub fn invalidate(&mut self) {
+        self.regex = OnceCell::new();
+    }
+
+    pub fn matches(&self, meta: &WorkflowMeta) -> bool {
+        let value = self.value.trim();
+        match self.kind {
+            RuleKind::NameGlob => glob_match(&value.to_lowercase(), &meta.workflow.name.to_lowercase()),
+            RuleKind::NameRegex => self.regex
+                .get_or_init(|| RegexBuilder::new(value).case_insensitive(true).build().ok())
+                .as_ref()
+                .map_or(false, |re| re.is_match(&meta.workflow.name)),
+            RuleKind::PathPrefix => meta.workflow.path.starts_with(value),
+            RuleKind::Trigger => meta.triggers.iter().any(|t| t == value),
+            RuleKind::HasInput => meta.inputs.iter().any(|i| i == value),
+            RuleKind::LastConclusion => meta.last_conclusion.as_deref() == Some(value),
+            // The API reports disabled workflows as `disabled_manually`, `disabled_inactivity`, ...
+            RuleKind::State => meta.workflow.state == value || (value == "disabled" && meta.workflow.state.starts_with("disabled")),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.value.trim().is_empty() {
+            return Err(format!("'{}' needs a value", self.kind.label()));
+        }
+        if self.kind == RuleKind::NameRegex {
+            RegexBuilder::new(self.value.trim()).build().map_err(|e| format!("Invalid regex '{}': {}", self.value, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A folder whose contents are computed from rules instead of filed by hand.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct SmartFolder {
+    pub name: String,
+    #[serde(default)]
+    pub rules: Vec<SmartRule>,
+    /// Every rule has to match when set, any one rule otherwise.
+    #[serde(default = "default_match_all")]
+    pub match_all: bool,
+}
+
+fn default_match_all() -> bool {
+    true
+}
+
+impl SmartFolder {
+    pub fn new(name: &str) -> Self {
+        SmartFolder { name: name.to_string(), rules: Vec::new(), match_all: true }
+    }
+
+    /// A folder without rules stays empty rather than matching everything.
+    pub fn matches(&self, meta: &WorkflowMeta) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        if self.match_all {
+            self.rules.iter().all(|rule| rule.matches(meta))
+        } else {
+            self.rules.iter().any(|rule| rule.matches(meta))
+        }
+    }
+
+    pub fn matching<'a>(&self, metas: &'a [WorkflowMeta]) -> Vec<&'a WorkflowMeta> {
+        metas.iter().filter(|meta| self.matches(meta)).collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Smart folders need a name".to_string());
+        }
+        self.rules.iter().try_for_each(SmartRule::validate)
+    }
+}
+
+/// `*` matches any run of characters and `?` a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeSet, HashMap};
+
+// Colors handed out to new tags in turn
+const TAG_PALETTE: [[u8; 3]; 8] = [
+    [97, 175, 239],
+    [152, 195, 121],
+    [229, 192, 123],
+    [224, 108, 117],
+    [198, 120, 221],
+    [86, 182, 194],
+    [209, 154, 102],
+    [171, 178, 191],
+];
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub color: [u8; 3],
+}
+
+/// Tags defined for the repository and which workflows (by id) carry them. Unlike folders,
+/// a workflow can carry any number of tags.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct TagStore {
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub assignments: HashMap<u64, BTreeSet<String>>,
+}
+
+impl TagStore {
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    pub fn next_color(&self) -> [u8; 3] {
+        TAG_PALETTE[self.tags.len() % TAG_PALETTE.len()]
+    }
+
+    pub fn define(&mut self, name: &str, color: [u8; 3]) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Tags need a name".to_string());
+        }
+        if self.get(name).is_some() {
+            return Err(format!("A tag named '{}' already exists", name));
+        }
+        self.tags.push(Tag { name: name.to_string(), color });
+        self.tags.sort_by_key(|tag| tag.name.to_lowercase());
+        Ok(())
+    }
+
+    /// Deletes a tag and takes it off every workflow.
+    pub fn delete(&mut self, name: &str) {
+        self.tags.retain(|tag| tag.name != name);
+        for names in self.assignments.values_mut() {
+            names.remove(name);
+        }
+        self.assignments.retain(|_, names| !names.is_empty());
+    }
+
+    /// Tags on a workflow, in the order they are defined.
+    pub fn tags_of(&self, id: u64) -> Vec<&Tag> {
+        match self.assignments.get(&id) {
+            Some(names) => self.tags.iter().filter(|tag| names.contains(&tag.name)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn has_tag(&self, id: u64, name: &str) -> bool {
+        self.assignments.get(&id).map_or(false, |names| names.contains(name))
+    }
+
+    pub fn tag(&mut self, ids: impl IntoIterator<Item = u64>, name: &str) {
+        if self.get(name).is_none() {
+            return;
+        }
+        for id in ids {
+            self.assignments.entry(id).or_default().insert(name.to_string());
+        }
+    }
+
+    pub fn untag(&mut self, ids: impl IntoIterator<Item = u64>, name: &str) {
+        for id in ids {
+            if let Some(names) = self.assignments.get_mut(&id) {
+                names.remove(name);
+                if names.is_empty() {
+                    self.assignments.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// Forgets assignments for workflows that no longer exist.
+    pub fn retain_workflows(&mut self, ids: &[u64]) {
+        self.assignments.retain(|id, _| ids.contains(id));
+    }
+
+    /// Whether a workflow passes the tag filter: with `match_all` it needs every selected tag,
+    /// otherwise any one of them. An empty filter lets everything through.
+    pub fn matches_filter(&self, id: u64, filter: &BTreeSet<String>, match_all: bool) -> bool {
+        if filter.is_empty() {
+            return true;
+        }
+        if match_all {
+            filter.iter().all(|name| self.has_tag(id, name))
+        } else {
+            filter.iter().any(|name| self.has_tag(id, name))
+        }
+    }
+}
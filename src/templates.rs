@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::editor::WORKFLOWS_DIR;
+
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("dispatch-with-inputs", include_str!("resources/templates/dispatch-with-inputs.yml")),
+    ("build-test", include_str!("resources/templates/build-test.yml")),
+    ("terraform-plan-apply", include_str!("resources/templates/terraform-plan-apply.yml")),
+    ("release", include_str!("resources/templates/release.yml")),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    pub key: String,
+    pub label: String,
+    pub default: String,
+}
+
+/// A workflow file with `{{key}}` placeholders, described by `# template:`, `# description:`
+/// and `# placeholder: key | label | default` header comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowTemplate {
+    pub name: String,
+    pub description: String,
+    pub placeholders: Vec<Placeholder>,
+    pub body: String,
+    pub builtin: bool,
+}
+
+impl WorkflowTemplate {
+    pub fn parse(default_name: &str, text: &str) -> Self {
+        let mut name = default_name.to_string();
+        let mut description = String::new();
+        let mut placeholders: Vec<Placeholder> = Vec::new();
+        let mut body_lines = Vec::new();
+
+        for line in text.lines() {
+            let header = line.strip_prefix('#').map(str::trim);
+            if let Some(value) = header.and_then(|h| h.strip_prefix("template:")) {
+                name = value.trim().to_string();
+            } else if let Some(value) = header.and_then(|h| h.strip_prefix("description:")) {
+                description = value.trim().to_string();
+            } else if let Some(value) = header.and_then(|h| h.strip_prefix("placeholder:")) {
+                let mut parts = value.split('|').map(str::trim);
+                let key = parts.next().unwrap_or_default().to_string();
+                let label = parts.next().filter(|l| !l.is_empty()).unwrap_or(&key).to_string();
+                let default = parts.next().unwrap_or_default().to_string();
+                if !key.is_empty() {
+                    placeholders.push(Placeholder { key, label, default });
+                }
+            } else {
+                body_lines.push(line);
+            }
+        }
+        let body = body_lines.join("\n") + "\n";
+
+        // Placeholders used in the body but not declared in the header still get a form field
+        for key in placeholder_keys(&body) {
+            if !placeholders.iter().any(|p| p.key == key) {
+                placeholders.push(Placeholder { label: key.clone(), key, default: String::new() });
+            }
+        }
+
+        WorkflowTemplate { name, description, placeholders, body, builtin: false }
+    }
+
+    pub fn default_values(&self) -> HashMap<String, String> {
+        self.placeholders.iter().map(|p| (p.key.clone(), p.default.clone())).collect()
+    }
+
+    /// Fills every `{{key}}` with its value. `${{ ... }}` workflow expressions are left alone.
+    pub fn render(&self, values: &HashMap<String, String>) -> String {
+        let mut output = String::with_capacity(self.body.len());
+        let mut rest = self.body.as_str();
+        while let Some(start) = rest.find("{{") {
+            let is_expression = rest[..start].ends_with('$');
+            let end = rest[start..].find("}}").map(|e| start + e);
+            match end {
+                Some(end) if !is_expression && is_placeholder_key(&rest[start + 2..end]) => {
+                    output.push_str(&rest[..start]);
+                    let key = &rest[start + 2..end];
+                    output.push_str(values.get(key).map(String::as_str).unwrap_or_default());
+                    rest = &rest[end + 2..];
+                },
+                _ => {
+                    output.push_str(&rest[..start + 2]);
+                    rest = &rest[start + 2..];
+                },
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+fn is_placeholder_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn placeholder_keys(body: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        if let Some(end) = after.find("}}") {
+            let key = &after[..end];
+            if !rest[..start].ends_with('$') && is_placeholder_key(key) && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+        rest = after;
+    }
+    keys
+}
+
+pub fn builtin_templates() -> Vec<WorkflowTemplate> {
+    BUILTIN_TEMPLATES.iter()
+        .map(|(name, text)| WorkflowTemplate { builtin: true, ..WorkflowTemplate::parse(name, text) })
+        .collect()
+}
+
+/// Loads team templates (`*.yml`/`*.yaml`) from a directory, sorted by template name.
+pub fn load_templates_dir(dir: &str) -> Result<Vec<WorkflowTemplate>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read templates from {}: {}", dir, e))?;
+    let mut templates = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !matches!(path.extension().and_then(|ext| ext.to_str()), Some("yml") | Some("yaml")) {
+            continue;
+        }
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        templates.push(WorkflowTemplate::parse(&stem, &text));
+    }
+    templates.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(templates)
+}
+
+/// Writes a new workflow into the clone's workflows folder and returns its path relative to the repo root.
+/// Refuses to overwrite an existing file.
+pub fn create_workflow_file(repo_path: &str, file_name: &str, contents: &str) -> Result<String, String> {
+    let file_name = file_name.trim();
+    if file_name.is_empty() || file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+        return Err(format!("'{}' is not a valid workflow file name", file_name));
+    }
+    let file_name = if file_name.ends_with(".yml") || file_name.ends_with(".yaml") {
+        file_name.to_string()
+    } else {
+        format!("{}.yml", file_name)
+    };
+
+    let workflows_dir = Path::new(repo_path).join(WORKFLOWS_DIR);
+    let file_path = workflows_dir.join(&file_name);
+    if file_path.exists() {
+        return Err(format!("{}/{} already exists", WORKFLOWS_DIR, file_name));
+    }
+    fs::create_dir_all(&workflows_dir).map_err(|e| format!("Failed to create {}: {}", WORKFLOWS_DIR, e))?;
+    fs::write(&file_path, contents).map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+    Ok(format!("{}/{}", WORKFLOWS_DIR, file_name))
+}
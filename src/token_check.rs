@@ -0,0 +1,105 @@
+/// Kind of GitHub token, told apart by its prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Classic,
+    FineGrained,
+    OAuth,
+    GitHubApp,
+    Unknown,
+}
+
+impl TokenKind {
+    pub fn from_token(token: &str) -> Self {
+        if token.starts_with("github_pat_") {
+            TokenKind::FineGrained
+        } else if token.starts_with("ghp_") {
+            TokenKind::Classic
+        } else if token.starts_with("gho_") || token.starts_with("ghu_") {
+            TokenKind::OAuth
+        } else if token.starts_with("ghs_") {
+            TokenKind::GitHubApp
+        } else {
+            TokenKind::Unknown
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TokenKind::Classic => "Classic personal access token",
+            TokenKind::FineGrained => "Fine-grained personal access token",
+            TokenKind::OAuth => "OAuth token",
+            TokenKind::GitHubApp => "GitHub App installation token",
+            TokenKind::Unknown => "Unknown token type",
+        }
+    }
+}
+
+/// The token's access to the configured repository, as `GET /repos/{repo}` reports it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoPermissions {
+    pub admin: bool,
+    pub push: bool,
+    pub pull: bool,
+}
+
+/// Result of the setup window's "Test connection".
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenReport {
+    pub user: String,
+    pub kind: TokenKind,
+    pub scopes: Option<Vec<String>>, // Only classic tokens and OAuth tokens report scopes
+    pub repo: Option<String>,
+    pub repo_permissions: Option<RepoPermissions>, // None when the repository is not visible
+    pub expires_at: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+impl TokenReport {
+    /// Builds the report from what the API returned and works out what the token is missing
+    /// for dispatching workflows (`actions:write`) and pushing (`contents:write`, `workflow`).
+    /// `scopes` is the raw `X-OAuth-Scopes` header.
+    pub fn new(token: &str, user: &str, scopes: Option<&str>, expires_at: Option<&str>, repo: Option<(&str, Option<RepoPermissions>)>) -> Self {
+        let kind = TokenKind::from_token(token);
+        let scopes: Option<Vec<String>> = scopes.map(|header| {
+            header.split(',').map(str::trim).filter(|scope| !scope.is_empty()).map(String::from).collect()
+        });
+        let mut warnings = Vec::new();
+
+        match &scopes {
+            Some(scopes) => {
+                let has = |scope: &str| scopes.iter().any(|granted| granted == scope);
+                if !has("repo") {
+                    warnings.push(if has("public_repo") {
+                        "Only the `public_repo` scope: private repositories can't be dispatched or pushed to".to_string()
+                    } else {
+                        "Missing the `repo` scope, needed to dispatch workflows (actions:write) and push (contents:write)".to_string()
+                    });
+                }
+                if !has("workflow") {
+                    warnings.push("Missing the `workflow` scope, needed to push changes to workflow files".to_string());
+                }
+            },
+            None => warnings.push(
+                "GitHub doesn't report fine-grained permissions; make sure Actions, Contents and Workflows are set to read and write".to_string()
+            ),
+        }
+
+        match &repo {
+            Some((repo, None)) => warnings.push(format!("{} is not visible with this token", repo)),
+            Some((repo, Some(permissions))) if !permissions.push => {
+                warnings.push(format!("No write access to {} (contents:write), so pushes and dispatches will fail", repo));
+            },
+            _ => {},
+        }
+
+        TokenReport {
+            user: user.to_string(),
+            kind,
+            scopes,
+            repo: repo.as_ref().map(|(repo, _)| repo.to_string()),
+            repo_permissions: repo.and_then(|(_, permissions)| permissions),
+            expires_at: expires_at.map(String::from),
+            warnings,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Current vault format. Bump it when the KDF or cipher changes so older vaults can still be opened.
+pub const VAULT_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+
+/// Argon2id cost parameters, stored with the vault so they can be raised later without
+/// breaking existing configs. The defaults follow the OWASP recommendation.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+}
+
+/// Key derived from the app password. Kept only in memory while the app is unlocked
+/// and wiped when dropped.
+#[derive(Clone)]
+pub struct VaultKey([u8; 32]);
+
+impl Drop for VaultKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// A secret (the GitHub PAT) encrypted with XChaCha20-Poly1305 under a key derived from the
+/// app password with Argon2id. The salt is generated once per install and kept across re-seals;
+/// every seal uses a fresh random nonce. Any change to the stored fields makes `open` fail.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Vault {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub salt: String,       // base64
+    pub nonce: String,      // base64
+    pub ciphertext: String, // base64, includes the authentication tag
+}
+
+/// Random salt for a new install, base64 encoded.
+pub fn new_salt() -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    STANDARD.encode(salt)
+}
+
+pub fn derive_key(password: &str, salt: &str, kdf: &KdfParams) -> Result<VaultKey, String> {
+    let salt = STANDARD.decode(salt).map_err(|e| format!("Invalid vault salt: {}", e))?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = VaultKey([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &salt, &mut key.0)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+// Binds the version and KDF settings to the ciphertext so they cannot be swapped undetected
+fn associated_data(version: u32, salt: &str, kdf: &KdfParams) -> Vec<u8> {
+    format!("actionallegro-vault:v{}:{}:{}:{}:{}", version, salt, kdf.memory_kib, kdf.iterations, kdf.parallelism).into_bytes()
+}
+
+impl Vault {
+    /// Encrypts `secret` with an already derived key. `salt` and `kdf` must be the ones the key was derived with.
+    pub fn seal(key: &VaultKey, salt: &str, kdf: &KdfParams, secret: &[u8]) -> Result<Vault, String> {
+        let cipher = XChaCha20Poly1305::new((&key.0).into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let aad = associated_data(VAULT_VERSION, salt, kdf);
+        let ciphertext = cipher.encrypt(&nonce, Payload { msg: secret, aad: &aad })
+            .map_err(|_| "Encryption failed".to_string())?;
+        Ok(Vault {
+            version: VAULT_VERSION,
+            kdf: kdf.clone(),
+            salt: salt.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the secret; the returned buffer is wiped when dropped.
+    pub fn open(&self, key: &VaultKey) -> Result<Zeroizing<Vec<u8>>, String> {
+        if self.version != VAULT_VERSION {
+            return Err(format!("Unsupported vault version {}", self.version));
+        }
+        let nonce = STANDARD.decode(&self.nonce).map_err(|e| format!("Invalid vault nonce: {}", e))?;
+        if nonce.len() != 24 {
+            return Err("Invalid vault nonce".to_string());
+        }
+        let ciphertext = STANDARD.decode(&self.ciphertext).map_err(|e| format!("Invalid vault data: {}", e))?;
+        let aad = associated_data(self.version, &self.salt, &self.kdf);
+        XChaCha20Poly1305::new((&key.0).into())
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+            .map(Zeroizing::new)
+            .map_err(|_| "Wrong password or the stored PAT was tampered with".to_string())
+    }
+
+    /// Derives the key from `password` and decrypts, returning the key for later re-seals.
+    pub fn unlock(&self, password: &str) -> Result<(VaultKey, Zeroizing<Vec<u8>>), String> {
+        let key = derive_key(password, &self.salt, &self.kdf)?;
+        let secret = self.open(&key)?;
+        Ok((key, secret))
+    }
+}
@@ -0,0 +1,561 @@
+use std::collections::HashMap;
+use serde_yaml::{Mapping, Value};
+
+/// GitHub refuses to start a job whose matrix expands past this many instances.
+pub const MATRIX_JOB_LIMIT: usize = 256;
+
+/// Typed view over the parts of a workflow file the app cares about.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workflow {
+    pub name: Option<String>,
+    pub triggers: Vec<String>,
+    pub dispatch_inputs: Vec<String>, // Input names under `on.workflow_dispatch.inputs`
+    pub jobs: Vec<Job>, // Kept in file order
+    pub call_contract: Option<CallContract>,
+}
+
+/// Inputs, secrets and outputs a reusable workflow declares under `on.workflow_call`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallContract {
+    pub inputs: Vec<ContractField>,
+    pub secrets: Vec<ContractField>,
+    pub outputs: Vec<ContractField>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContractField {
+    pub name: String,
+    pub description: String,
+    pub field_type: Option<String>,
+    pub required: bool,
+    pub default: Option<String>,
+    pub value: Option<String>, // Only set for outputs
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Job {
+    pub id: String,
+    pub name: Option<String>,
+    pub runs_on: Option<String>,
+    pub needs: Vec<String>,
+    pub if_condition: Option<String>,
+    pub matrix: Option<Value>,
+    pub uses: Option<String>,
+    pub step_uses: Vec<String>, // `uses:` of each step, in order
+    pub called_from: Option<String>, // Set on jobs pulled in from a local reusable workflow
+}
+
+/// Status of a job in a selected run, as reported by the GitHub API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    InProgress,
+    Success,
+    Failure,
+    Cancelled,
+    Skipped,
+}
+
+impl Workflow {
+    pub fn from_yaml(yaml: &Value) -> Self {
+        let name = yaml.get("name").and_then(|n| n.as_str()).map(String::from);
+
+        let triggers = match yaml.get("on") {
+            Some(Value::String(trigger)) => vec![trigger.clone()],
+            Some(Value::Sequence(seq)) => seq.iter().filter_map(|t| t.as_str()).map(String::from).collect(),
+            Some(Value::Mapping(map)) => map.keys().filter_map(|t| t.as_str()).map(String::from).collect(),
+            _ => Vec::new(),
+        };
+
+        let dispatch_inputs = yaml.get("on")
+            .and_then(|on| on.get("workflow_dispatch"))
+            .and_then(|dispatch| dispatch.get("inputs"))
+            .and_then(|inputs| inputs.as_mapping())
+            .map(|inputs| inputs.keys().filter_map(|k| k.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+
+        let mut jobs = Vec::new();
+        if let Some(job_map) = yaml.get("jobs").and_then(|j| j.as_mapping()) {
+            for (job_id, details) in job_map {
+                if let Some(id) = job_id.as_str() {
+                    jobs.push(Job::from_yaml(id, details));
+                }
+            }
+        }
+
+        let call_contract = match yaml.get("on") {
+            Some(on) if triggers.iter().any(|t| t == "workflow_call") => Some(CallContract::from_yaml(on.get("workflow_call"))),
+            _ => None,
+        };
+
+        Workflow { name, triggers, dispatch_inputs, jobs, call_contract }
+    }
+
+    /// Replaces every job that calls a local reusable workflow (`uses: ./.github/workflows/x.yml`)
+    /// with the jobs of that workflow, so the graph shows what actually runs.
+    /// `load` returns the parsed workflow for a repository relative path.
+    pub fn with_local_calls_expanded(&self, load: &impl Fn(&str) -> Option<Workflow>) -> Workflow {
+        self.expand_local_calls(load, 0)
+    }
+
+    fn expand_local_calls(&self, load: &impl Fn(&str) -> Option<Workflow>, depth: usize) -> Workflow {
+        // GitHub allows reusable workflows to be nested up to four levels deep
+        const MAX_DEPTH: usize = 4;
+
+        let mut expanded = Workflow { jobs: Vec::new(), ..self.clone() };
+        // Caller job id -> ids of the callee jobs that finish last, for rewiring `needs`
+        let mut replaced: HashMap<String, Vec<String>> = HashMap::new();
+
+        for job in &self.jobs {
+            let callee = job.local_workflow_path()
+                .filter(|_| depth < MAX_DEPTH)
+                .and_then(|path| load(path).map(|callee| (path, callee.expand_local_calls(load, depth + 1))));
+            let Some((path, callee)) = callee else {
+                expanded.jobs.push(job.clone());
+                continue;
+            };
+
+            let prefixed = |id: &str| format!("{}/{}", job.id, id);
+            let mut leaves = Vec::new();
+            for callee_job in &callee.jobs {
+                let needs = if callee_job.needs.is_empty() {
+                    job.needs.clone()
+                } else {
+                    callee_job.needs.iter().map(|need| prefixed(need)).collect()
+                };
+                if !callee.jobs.iter().any(|other| other.needs.contains(&callee_job.id)) {
+                    leaves.push(prefixed(&callee_job.id));
+                }
+                expanded.jobs.push(Job {
+                    id: prefixed(&callee_job.id),
+                    name: Some(format!("{} / {}", job.display_name(), callee_job.display_name())),
+                    needs,
+                    called_from: Some(callee_job.called_from.clone().unwrap_or_else(|| path.to_string())),
+                    ..callee_job.clone()
+                });
+            }
+            replaced.insert(job.id.clone(), leaves);
+        }
+
+        for job in expanded.jobs.iter_mut() {
+            job.needs = job.needs.iter()
+                .flat_map(|need| replaced.get(need).cloned().unwrap_or_else(|| vec![need.clone()]))
+                .collect();
+        }
+        expanded
+    }
+
+    /// Groups job indexes into columns so every job sits to the right of everything it `needs`.
+    /// Jobs caught in a dependency cycle (or needing unknown jobs) fall back to the first column.
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let index: HashMap<&str, usize> = self.jobs.iter().enumerate().map(|(i, job)| (job.id.as_str(), i)).collect();
+        let mut depth: Vec<Option<usize>> = vec![None; self.jobs.len()];
+
+        fn visit(i: usize, jobs: &[Job], index: &HashMap<&str, usize>, depth: &mut Vec<Option<usize>>, stack: &mut Vec<usize>) -> usize {
+            if let Some(d) = depth[i] {
+                return d;
+            }
+            if stack.contains(&i) {
+                return 0; // Cycle
+            }
+            stack.push(i);
+            let d = jobs[i].needs.iter()
+                .filter_map(|need| index.get(need.as_str()))
+                .map(|&n| visit(n, jobs, index, depth, stack) + 1)
+                .max()
+                .unwrap_or(0);
+            stack.pop();
+            depth[i] = Some(d);
+            d
+        }
+
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.jobs.len() {
+            let d = visit(i, &self.jobs, &index, &mut depth, &mut Vec::new());
+            if layers.len() <= d {
+                layers.resize(d + 1, Vec::new());
+            }
+            layers[d].push(i);
+        }
+        layers
+    }
+
+    /// Returns the job ids along the first `needs` cycle found, ending on the job it started from.
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        fn visit<'a>(job: &'a Job, workflow: &'a Workflow, path: &mut Vec<&'a str>, done: &mut Vec<&'a str>) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|id| *id == job.id) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+                cycle.push(job.id.clone());
+                return Some(cycle);
+            }
+            if done.contains(&job.id.as_str()) {
+                return None;
+            }
+            path.push(&job.id);
+            for need in &job.needs {
+                if let Some(next) = workflow.jobs.iter().find(|other| &other.id == need) {
+                    if let Some(cycle) = visit(next, workflow, path, done) {
+                        return Some(cycle);
+                    }
+                }
+            }
+            path.pop();
+            done.push(&job.id);
+            None
+        }
+
+        let mut done = Vec::new();
+        self.jobs.iter().find_map(|job| visit(job, self, &mut Vec::new(), &mut done))
+    }
+}
+
+impl Job {
+    fn from_yaml(id: &str, details: &Value) -> Self {
+        let needs = match details.get("needs") {
+            Some(Value::String(need)) => vec![need.clone()],
+            Some(Value::Sequence(seq)) => seq.iter().filter_map(|n| n.as_str()).map(String::from).collect(),
+            _ => Vec::new(),
+        };
+
+        let runs_on = match details.get("runs-on") {
+            Some(Value::String(runner)) => Some(runner.clone()),
+            Some(Value::Sequence(labels)) => Some(labels.iter().filter_map(|l| l.as_str()).collect::<Vec<_>>().join(", ")),
+            Some(Value::Mapping(map)) => map.get("group").or_else(|| map.get("labels")).map(yaml_to_display),
+            _ => None,
+        };
+
+        Job {
+            id: id.to_string(),
+            name: details.get("name").and_then(|n| n.as_str()).map(String::from),
+            runs_on,
+            needs,
+            if_condition: details.get("if").map(yaml_to_display),
+            matrix: details.get("strategy").and_then(|s| s.get("matrix")).cloned(),
+            uses: details.get("uses").and_then(|u| u.as_str()).map(String::from),
+            step_uses: details.get("steps").and_then(|s| s.as_sequence()).into_iter().flatten()
+                .filter_map(|step| step.get("uses").and_then(|u| u.as_str()).map(String::from))
+                .collect(),
+            called_from: None,
+        }
+    }
+
+    /// Repository relative path of the reusable workflow this job calls, if it lives in the same repository.
+    pub fn local_workflow_path(&self) -> Option<&str> {
+        self.uses.as_deref()?.strip_prefix("./")
+    }
+
+    /// Name GitHub shows for this job in a run (the `name:` if set, otherwise the job id).
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Number of job instances the matrix produces without any dispatch inputs.
+    /// Returns `None` when the job has no matrix or the matrix depends on an input.
+    pub fn matrix_size(&self) -> Option<usize> {
+        self.matrix.as_ref()?;
+        self.expand_matrix(&HashMap::new()).ok().map(|combos| combos.len())
+    }
+
+    /// Expands `strategy.matrix` into the concrete combinations GitHub would schedule.
+    /// `fromJSON(inputs.x)` expressions are resolved against the given dispatch input values.
+    pub fn expand_matrix(&self, inputs: &HashMap<String, String>) -> Result<Vec<Mapping>, String> {
+        let Some(matrix) = &self.matrix else { return Ok(Vec::new()) };
+        let matrix = resolve_expression(matrix, inputs)?;
+        let matrix = matrix.as_mapping().ok_or("Matrix is not a mapping")?;
+
+        let mut axes: Vec<(Value, Vec<Value>)> = Vec::new();
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for (key, values) in matrix {
+            let values = resolve_expression(values, inputs)?;
+            match key.as_str() {
+                Some("include") => include = combination_list(&values)?,
+                Some("exclude") => exclude = combination_list(&values)?,
+                _ => {
+                    let values = values.as_sequence()
+                        .cloned()
+                        .ok_or_else(|| format!("Matrix axis '{}' is not a list", yaml_to_display(key)))?;
+                    axes.push((key.clone(), values));
+                }
+            }
+        }
+
+        // Cartesian product of every axis, in the order the axes are declared
+        let mut combos = if axes.is_empty() { Vec::new() } else { vec![Mapping::new()] };
+        for (key, values) in &axes {
+            combos = combos.iter()
+                .flat_map(|combo| values.iter().map(move |value| {
+                    let mut combo = combo.clone();
+                    combo.insert(key.clone(), value.clone());
+                    combo
+                }))
+                .collect();
+        }
+
+        combos.retain(|combo| !exclude.iter().any(|excluded| is_subset(excluded, combo)));
+
+        // An include extends every combination from the cross product whose original axis values
+        // it matches, otherwise it is added as a combination of its own. Combinations added by
+        // earlier includes are never extended.
+        let original = combos.len();
+        for extra in include {
+            if axes.is_empty() {
+                combos.push(extra);
+                continue;
+            }
+            let mut matched = false;
+            for combo in combos[..original].iter_mut() {
+                let matches_axes = extra.iter()
+                    .filter(|(key, _)| axes.iter().any(|(axis, _)| axis == *key))
+                    .all(|(key, value)| combo.get(key) == Some(value));
+                if matches_axes {
+                    for (key, value) in &extra {
+                        combo.insert(key.clone(), value.clone());
+                    }
+                    matched = true;
+                }
+            }
+            if !matched {
+                combos.push(extra);
+            }
+        }
+
+        Ok(combos)
+    }
+}
+
+const WORKFLOW_KEYS: &[&str] = &["name", "run-name", "on", "permissions", "env", "defaults", "concurrency", "jobs"];
+const INPUT_TYPES: &[&str] = &["string", "choice", "boolean", "number", "environment"];
+
+/// Checks workflow YAML against the typed model and the parts of GitHub's schema the app relies on.
+/// Returns one human readable message per problem; an empty list means the file looks valid.
+pub fn validate_workflow(text: &str) -> Vec<String> {
+    let yaml = match serde_yaml::from_str::<Value>(text) {
+        Ok(yaml) => yaml,
+        Err(e) => return vec![format!("YAML syntax error: {}", e)],
+    };
+    let Some(top_level) = yaml.as_mapping() else {
+        return vec!["Workflow must be a mapping at the top level".to_string()];
+    };
+
+    let mut problems = Vec::new();
+    for key in top_level.keys() {
+        let key = yaml_to_display(key);
+        if !WORKFLOW_KEYS.contains(&key.as_str()) {
+            problems.push(format!("Unknown top-level key '{}'", key));
+        }
+    }
+    if yaml.get("on").is_none() {
+        problems.push("Missing 'on' trigger section".to_string());
+    }
+
+    if let Some(inputs) = yaml.get("on").and_then(|on| on.get("workflow_dispatch")).and_then(|d| d.get("inputs")).and_then(|i| i.as_mapping()) {
+        for (name, details) in inputs {
+            let name = yaml_to_display(name);
+            let input_type = details.get("type").and_then(|t| t.as_str()).unwrap_or("string");
+            if !INPUT_TYPES.contains(&input_type) {
+                problems.push(format!("Input '{}' has unknown type '{}'", name, input_type));
+            }
+            if input_type == "choice" {
+                match details.get("options").and_then(|o| o.as_sequence()) {
+                    Some(options) if !options.is_empty() => {
+                        if let Some(default) = details.get("default") {
+                            if !options.contains(default) {
+                                problems.push(format!("Default of input '{}' is not one of its options", name));
+                            }
+                        }
+                    },
+                    _ => problems.push(format!("Choice input '{}' needs a non-empty 'options' list", name)),
+                }
+            }
+        }
+    }
+
+    let workflow = Workflow::from_yaml(&yaml);
+    if workflow.jobs.is_empty() {
+        problems.push("Workflow has no jobs".to_string());
+    }
+    for job in &workflow.jobs {
+        let details = &yaml["jobs"][job.id.as_str()];
+        if job.uses.is_none() && job.runs_on.is_none() {
+            problems.push(format!("Job '{}' needs either 'runs-on' or 'uses'", job.id));
+        }
+        if job.uses.is_some() && details.get("steps").is_some() {
+            problems.push(format!("Job '{}' calls a reusable workflow and cannot also have 'steps'", job.id));
+        }
+        for need in &job.needs {
+            if !workflow.jobs.iter().any(|other| &other.id == need) {
+                problems.push(format!("Job '{}' needs unknown job '{}'", job.id, need));
+            }
+        }
+        match details.get("steps") {
+            Some(Value::Sequence(steps)) => {
+                for (i, step) in steps.iter().enumerate() {
+                    match (step.get("uses").is_some(), step.get("run").is_some()) {
+                        (false, false) => problems.push(format!("Step {} of job '{}' needs either 'uses' or 'run'", i + 1, job.id)),
+                        (true, true) => problems.push(format!("Step {} of job '{}' cannot have both 'uses' and 'run'", i + 1, job.id)),
+                        _ => {}
+                    }
+                }
+            },
+            Some(_) => problems.push(format!("'steps' of job '{}' must be a list", job.id)),
+            None => {}
+        }
+        if job.matrix.is_some() {
+            if let Some(size) = job.matrix_size() {
+                if size > MATRIX_JOB_LIMIT {
+                    problems.push(format!("Matrix of job '{}' expands to {} jobs (limit is {})", job.id, size, MATRIX_JOB_LIMIT));
+                }
+            }
+        }
+    }
+    if let Some(cycle) = workflow.find_cycle() {
+        problems.push(format!("Jobs depend on each other in a cycle: {}", cycle.join(" -> ")));
+    }
+
+    problems
+}
+
+impl CallContract {
+    fn from_yaml(workflow_call: Option<&Value>) -> Self {
+        let fields = |section: &str| -> Vec<ContractField> {
+            let Some(map) = workflow_call.and_then(|w| w.get(section)).and_then(|s| s.as_mapping()) else { return Vec::new() };
+            map.iter()
+                .map(|(name, details)| ContractField {
+                    name: yaml_to_display(name),
+                    description: details.get("description").map(yaml_to_display).unwrap_or_default(),
+                    field_type: details.get("type").map(yaml_to_display),
+                    required: details.get("required").and_then(|r| r.as_bool()).unwrap_or(false),
+                    default: details.get("default").map(yaml_to_display),
+                    value: details.get("value").map(yaml_to_display),
+                })
+                .collect()
+        };
+
+        CallContract {
+            inputs: fields("inputs"),
+            secrets: fields("secrets"),
+            outputs: fields("outputs"),
+        }
+    }
+}
+
+/// Finds the jobs in `workflows` (repository relative path, parsed workflow) that call the
+/// reusable workflow at `target_path`, either locally or through `owner/repo/<path>@ref`.
+/// Returns (caller path, job id) pairs.
+pub fn find_callers(workflows: &[(String, Workflow)], target_path: &str, repo_slug: &str) -> Vec<(String, String)> {
+    let remote_prefix = format!("{}/{}@", repo_slug, target_path).to_lowercase();
+    let mut callers = Vec::new();
+    for (path, workflow) in workflows {
+        for job in &workflow.jobs {
+            let calls_target = job.local_workflow_path() == Some(target_path)
+                || job.uses.as_deref().map_or(false, |uses| uses.to_lowercase().starts_with(&remote_prefix));
+            if calls_target {
+                callers.push((path.clone(), job.id.clone()));
+            }
+        }
+    }
+    callers
+}
+
+/// Short `key: value, ...` label for one matrix combination.
+pub fn combination_label(combo: &Mapping) -> String {
+    combo.iter()
+        .map(|(key, value)| format!("{}: {}", yaml_to_display(key), yaml_to_display(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn combination_list(values: &Value) -> Result<Vec<Mapping>, String> {
+    match values {
+        Value::Sequence(seq) => seq.iter()
+            .map(|entry| entry.as_mapping().cloned().ok_or_else(|| "include/exclude entries must be mappings".to_string()))
+            .collect(),
+        Value::Null => Ok(Vec::new()),
+        _ => Err("include/exclude must be a list".to_string()),
+    }
+}
+
+fn is_subset(subset: &Mapping, combo: &Mapping) -> bool {
+    subset.iter().all(|(key, value)| combo.get(key) == Some(value))
+}
+
+/// Replaces a `${{ fromJSON(inputs.name) }}` string with the parsed input value.
+/// Any other value is returned untouched; other expressions cannot be previewed.
+fn resolve_expression(value: &Value, inputs: &HashMap<String, String>) -> Result<Value, String> {
+    let Some(text) = value.as_str() else { return Ok(value.clone()) };
+    let Some(expression) = text.trim().strip_prefix("${{").and_then(|e| e.strip_suffix("}}")) else {
+        return Ok(value.clone());
+    };
+    let expression = expression.trim();
+
+    let argument = expression.strip_prefix("fromJSON(")
+        .or_else(|| expression.strip_prefix("fromJson("))
+        .and_then(|e| e.strip_suffix(')'))
+        .map(str::trim)
+        .ok_or_else(|| format!("Cannot preview expression '{}'", expression))?;
+    let input_name = argument.strip_prefix("github.event.inputs.")
+        .or_else(|| argument.strip_prefix("inputs."))
+        .ok_or_else(|| format!("Cannot preview expression '{}'", expression))?;
+
+    let raw = inputs.get(input_name)
+        .filter(|raw| !raw.trim().is_empty())
+        .ok_or_else(|| format!("Input '{}' is empty", input_name))?;
+    serde_yaml::from_str::<Value>(raw).map_err(|e| format!("Input '{}' is not valid JSON: {}", input_name, e))
+}
+
+impl JobState {
+    pub fn from_api(status: &str, conclusion: Option<&str>) -> Self {
+        match (status, conclusion) {
+            ("completed", Some("success")) => JobState::Success,
+            ("completed", Some("skipped")) => JobState::Skipped,
+            ("completed", Some("cancelled")) => JobState::Cancelled,
+            ("completed", _) => JobState::Failure,
+            ("in_progress", _) => JobState::InProgress,
+            _ => JobState::Queued,
+        }
+    }
+
+    // Lower ranks win when several run jobs (e.g. matrix legs) map onto one graph node
+    fn rank(self) -> u8 {
+        match self {
+            JobState::Failure => 0,
+            JobState::InProgress => 1,
+            JobState::Queued => 2,
+            JobState::Cancelled => 3,
+            JobState::Success => 4,
+            JobState::Skipped => 5,
+        }
+    }
+}
+
+/// Maps the jobs of a run (by their reported names) onto workflow job ids.
+/// Matrix legs are reported as `name (a, b)` and reusable workflow jobs as `name / inner`.
+pub fn job_states_for_run(workflow: &Workflow, run_jobs: &[(String, JobState)]) -> HashMap<String, JobState> {
+    let mut states: HashMap<String, JobState> = HashMap::new();
+    for job in &workflow.jobs {
+        let display = job.display_name();
+        for (run_job_name, state) in run_jobs {
+            let matches = run_job_name == display
+                || run_job_name.starts_with(&format!("{} (", display))
+                || run_job_name.starts_with(&format!("{} / ", display));
+            if matches {
+                let entry = states.entry(job.id.clone()).or_insert(*state);
+                if state.rank() < entry.rank() {
+                    *entry = *state;
+                }
+            }
+        }
+    }
+    states
+}
+
+fn yaml_to_display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Sequence(seq) => seq.iter().map(yaml_to_display).collect::<Vec<_>>().join(", "),
+        other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+    }
+}
@@ -0,0 +1,87 @@
+use crate::folders::FolderNode;
+use crate::quick_access::QuickAccess;
+use crate::smart_folders::SmartFolder;
+use crate::tags::TagStore;
+
+/// Name shown for the root of the cross-repository folder tree.
+pub const WORKSPACE_ROOT_NAME: &str = "Workspace";
+
+/// Everything that belongs to one repository of the workspace. The active repository's
+/// copy lives on the app itself and is written back here when switching away from it.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct RepoProfile {
+    pub repo_name: String,
+    #[serde(default)]
+    pub repo_path: Option<String>,
+    #[serde(default)]
+    pub selected_branch: String,
+    #[serde(default)]
+    pub folder_tree: FolderNode,
+    #[serde(default)]
+    pub tags: TagStore,
+    #[serde(default)]
+    pub smart_folders: Vec<SmartFolder>,
+    #[serde(default)]
+    pub quick_access: QuickAccess,
+}
+
+impl RepoProfile {
+    pub fn new(repo_name: &str) -> Self {
+        RepoProfile { repo_name: repo_name.to_string(), ..Default::default() }
+    }
+}
+
+/// The repositories a user works with, plus folders that mix workflows from several of them.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Workspace {
+    #[serde(default)]
+    pub repos: Vec<RepoProfile>,
+    #[serde(default = "workspace_root")]
+    pub folders: FolderNode,
+}
+
+fn workspace_root() -> FolderNode {
+    FolderNode::new(WORKSPACE_ROOT_NAME)
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace { repos: Vec::new(), folders: workspace_root() }
+    }
+}
+
+impl Workspace {
+    pub fn find(&self, repo_name: &str) -> Option<&RepoProfile> {
+        self.repos.iter().find(|profile| profile.repo_name == repo_name)
+    }
+
+    pub fn add_repo(&mut self, repo_name: &str) -> Result<(), String> {
+        let repo_name = repo_name.trim();
+        let valid = matches!(repo_name.split_once('/'), Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/'));
+        if !valid {
+            return Err(format!("'{}' is not an owner/repo name", repo_name));
+        }
+        if self.find(repo_name).is_some() {
+            return Err(format!("{} is already in the workspace", repo_name));
+        }
+        self.repos.push(RepoProfile::new(repo_name));
+        Ok(())
+    }
+
+    /// Removes a repository and its workflows from the workspace folders.
+    pub fn remove_repo(&mut self, repo_name: &str) {
+        self.repos.retain(|profile| profile.repo_name != repo_name);
+        self.folders.sync_repo(repo_name, &[]);
+    }
+
+    /// Saves a profile, replacing the stored one for the same repository.
+    pub fn store(&mut self, profile: RepoProfile) {
+        if profile.repo_name.is_empty() {
+            return;
+        }
+        match self.repos.iter_mut().find(|existing| existing.repo_name == profile.repo_name) {
+            Some(existing) => *existing = profile,
+            None => self.repos.push(profile),
+        }
+    }
+}